@@ -0,0 +1,430 @@
+use std::rc::Rc;
+
+use halo2_curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{self, Circuit, ConstraintSystem, ProvingKey},
+    poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+};
+use itertools::Itertools;
+use rand::rngs::OsRng;
+use snark_verifier::{
+    loader::{
+        self,
+        halo2::halo2_wrong_ecc::{
+            self,
+            integer::rns::Rns,
+            maingate::{
+                MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig,
+                RangeInstructions, RegionCtx,
+            },
+            EccConfig,
+        },
+        native::NativeLoader,
+    },
+    pcs::{
+        kzg::{Gwc19, KzgAccumulator, KzgAs, KzgSuccinctVerifyingKey, LimbsEncodingInstructions},
+        AccumulationScheme, AccumulationSchemeProver,
+    },
+    system::halo2::{compile, transcript::halo2, Config},
+    util::arithmetic::fe_to_limbs,
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
+};
+
+use crate::generator::{gen_proof, Error, MultiOpen, Transcript};
+
+/// Number of limbs of an accumulator coordinate exposed as instances.
+pub const LIMBS: usize = 4;
+/// Bit size of each accumulator limb.
+pub const BITS: usize = 68;
+
+const T: usize = 5;
+const RATE: usize = 4;
+const R_F: usize = 8;
+const R_P: usize = 60;
+
+type As = KzgAs<Bn256, Gwc19>;
+type PlonkSuccinctVerifier = verifier::plonk::PlonkSuccinctVerifier<As>;
+type Svk = KzgSuccinctVerifyingKey<G1Affine>;
+type BaseFieldEccChip = halo2_wrong_ecc::BaseFieldEccChip<G1Affine, LIMBS, BITS>;
+type Halo2Loader<'a> = loader::halo2::Halo2Loader<'a, G1Affine, BaseFieldEccChip>;
+
+/// Poseidon transcript which can be verified natively and inside the aggregation circuit.
+pub type PoseidonTranscript<L, S> = halo2::PoseidonTranscript<G1Affine, L, S, T, RATE, R_F, R_P>;
+
+/// Proof to be aggregated, created with GWC and [`Transcript::Poseidon`].
+#[derive(Clone)]
+pub struct Snark {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Fr>>,
+    proof: Vec<u8>,
+}
+
+impl Snark {
+    pub fn new(protocol: PlonkProtocol<G1Affine>, instances: Vec<Vec<Fr>>, proof: Vec<u8>) -> Self {
+        Self {
+            protocol,
+            instances,
+            proof,
+        }
+    }
+}
+
+/// Proves `circuit` with GWC and [`Transcript::Poseidon`] so it can be aggregated.
+pub fn gen_snark<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+) -> Result<Snark, Error> {
+    let protocol = compile(
+        params,
+        pk.get_vk(),
+        Config::kzg().with_num_instance(instances.iter().map(Vec::len).collect()),
+    );
+
+    let proof = gen_proof(
+        params,
+        pk,
+        circuit,
+        &instances,
+        MultiOpen::Gwc,
+        Transcript::Poseidon,
+    )?;
+
+    Ok(Snark::new(protocol, instances, proof))
+}
+
+#[derive(Clone)]
+struct SnarkWitness {
+    protocol: PlonkProtocol<G1Affine>,
+    instances: Vec<Vec<Value<Fr>>>,
+    proof: Value<Vec<u8>>,
+}
+
+impl From<Snark> for SnarkWitness {
+    fn from(snark: Snark) -> Self {
+        Self {
+            protocol: snark.protocol,
+            instances: snark
+                .instances
+                .into_iter()
+                .map(|instances| instances.into_iter().map(Value::known).collect_vec())
+                .collect(),
+            proof: Value::known(snark.proof),
+        }
+    }
+}
+
+impl SnarkWitness {
+    fn without_witnesses(&self) -> Self {
+        SnarkWitness {
+            protocol: self.protocol.clone(),
+            instances: self
+                .instances
+                .iter()
+                .map(|instances| vec![Value::unknown(); instances.len()])
+                .collect(),
+            proof: Value::unknown(),
+        }
+    }
+
+    fn proof(&self) -> Value<&[u8]> {
+        self.proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+/// Verifies `snarks` in circuit and folds their accumulators into one.
+fn aggregate<'a>(
+    svk: &Svk,
+    loader: &Rc<Halo2Loader<'a>>,
+    snarks: &[SnarkWitness],
+    as_proof: Value<&'_ [u8]>,
+) -> Result<KzgAccumulator<G1Affine, Rc<Halo2Loader<'a>>>, snark_verifier::Error> {
+    let assign_instances = |instances: &[Vec<Value<Fr>>]| {
+        instances
+            .iter()
+            .map(|instances| {
+                instances
+                    .iter()
+                    .map(|instance| loader.assign_scalar(*instance))
+                    .collect_vec()
+            })
+            .collect_vec()
+    };
+
+    let mut accumulators = Vec::new();
+    for snark in snarks {
+        let protocol = snark.protocol.loaded(loader);
+        let instances = assign_instances(&snark.instances);
+        let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, snark.proof());
+        let proof = PlonkSuccinctVerifier::read_proof(svk, &protocol, &instances, &mut transcript)?;
+        accumulators.extend(PlonkSuccinctVerifier::verify(
+            svk, &protocol, &instances, &proof,
+        )?);
+    }
+
+    let mut transcript = PoseidonTranscript::<Rc<Halo2Loader>, _>::new(loader, as_proof);
+    let proof = As::read_proof(&Default::default(), &accumulators, &mut transcript)?;
+    As::verify(&Default::default(), &accumulators, &proof)
+}
+
+#[derive(Clone)]
+pub struct AggregationConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl AggregationConfig {
+    pub fn configure(
+        meta: &mut ConstraintSystem<Fr>,
+        composition_bits: Vec<usize>,
+        overflow_bits: Vec<usize>,
+    ) -> Self {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let range_config =
+            RangeChip::<Fr>::configure(meta, &main_gate_config, composition_bits, overflow_bits);
+        AggregationConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    pub fn main_gate(&self) -> MainGate<Fr> {
+        MainGate::new(self.main_gate_config.clone())
+    }
+
+    pub fn range_chip(&self) -> RangeChip<Fr> {
+        RangeChip::new(self.range_config.clone())
+    }
+
+    pub fn ecc_chip(&self) -> BaseFieldEccChip {
+        BaseFieldEccChip::new(EccConfig::new(
+            self.range_config.clone(),
+            self.main_gate_config.clone(),
+        ))
+    }
+}
+
+/// Proves that all aggregated snarks verify, leaving the final KZG pairing check to the
+/// verifier through the accumulator limbs exposed in the only instance column.
+#[derive(Clone)]
+pub struct AggregationCircuit {
+    svk: Svk,
+    snarks: Vec<SnarkWitness>,
+    instances: Vec<Fr>,
+    as_proof: Value<Vec<u8>>,
+}
+
+impl AggregationCircuit {
+    /// Aggregates `snarks`, `params` must be the params they were created with.
+    pub fn new(
+        params: &ParamsKZG<Bn256>,
+        snarks: impl IntoIterator<Item = Snark>,
+    ) -> Result<Self, Error> {
+        let svk = params.get_g()[0].into();
+        let snarks = snarks.into_iter().collect_vec();
+
+        let mut accumulators = Vec::new();
+        for snark in snarks.iter() {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(snark.proof.as_slice());
+            let proof = PlonkSuccinctVerifier::read_proof(
+                &svk,
+                &snark.protocol,
+                &snark.instances,
+                &mut transcript,
+            )?;
+            accumulators.extend(PlonkSuccinctVerifier::verify(
+                &svk,
+                &snark.protocol,
+                &snark.instances,
+                &proof,
+            )?);
+        }
+
+        let (accumulator, as_proof) = {
+            let mut transcript = PoseidonTranscript::<NativeLoader, _>::new(Vec::new());
+            let accumulator =
+                As::create_proof(&Default::default(), &accumulators, &mut transcript, OsRng)?;
+            (accumulator, transcript.finalize())
+        };
+
+        let KzgAccumulator { lhs, rhs } = accumulator;
+        let instances = [lhs.x, lhs.y, rhs.x, rhs.y]
+            .map(fe_to_limbs::<_, _, LIMBS, BITS>)
+            .concat();
+
+        Ok(Self {
+            svk,
+            snarks: snarks.into_iter().map_into().collect(),
+            instances,
+            as_proof: Value::known(as_proof),
+        })
+    }
+
+    /// Positions of the accumulator limbs, `(column, row)` in the instances.
+    pub fn accumulator_indices() -> Vec<(usize, usize)> {
+        (0..4 * LIMBS).map(|idx| (0, idx)).collect()
+    }
+
+    pub fn num_instance() -> Vec<usize> {
+        vec![4 * LIMBS]
+    }
+
+    pub fn instances(&self) -> Vec<Vec<Fr>> {
+        vec![self.instances.clone()]
+    }
+
+    fn as_proof(&self) -> Value<&[u8]> {
+        self.as_proof.as_ref().map(Vec::as_slice)
+    }
+}
+
+impl Circuit<Fr> for AggregationCircuit {
+    type Config = AggregationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+    #[cfg(feature = "circuit-params")]
+    type Params = ();
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            svk: self.svk,
+            snarks: self
+                .snarks
+                .iter()
+                .map(SnarkWitness::without_witnesses)
+                .collect(),
+            instances: Vec::new(),
+            as_proof: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        AggregationConfig::configure(
+            meta,
+            vec![BITS / LIMBS],
+            Rns::<Fq, Fr, LIMBS, BITS>::construct().overflow_lengths(),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), plonk::Error> {
+        let main_gate = config.main_gate();
+        let range_chip = config.range_chip();
+
+        range_chip.load_table(&mut layouter)?;
+
+        let accumulator_limbs = layouter.assign_region(
+            || "aggregate",
+            |region| {
+                let ctx = RegionCtx::new(region, 0);
+
+                let ecc_chip = config.ecc_chip();
+                let loader = Halo2Loader::new(ecc_chip, ctx);
+                let accumulator = aggregate(&self.svk, &loader, &self.snarks, self.as_proof())
+                    .map_err(|_| plonk::Error::Synthesis)?;
+
+                let accumulator_limbs = [accumulator.lhs, accumulator.rhs]
+                    .iter()
+                    .map(|ec_point| {
+                        loader
+                            .ecc_chip()
+                            .assign_ec_point_to_limbs(&mut loader.ctx_mut(), ec_point.assigned())
+                    })
+                    .collect::<Result<Vec<_>, plonk::Error>>()?
+                    .into_iter()
+                    .flatten();
+
+                Ok(accumulator_limbs)
+            },
+        )?;
+
+        for (row, limb) in accumulator_limbs.enumerate() {
+            main_gate.expose_public(layouter.namespace(|| "expose accumulator"), limb, row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    use super::{gen_snark, AggregationCircuit};
+    use crate::{
+        circuits::simple::SimpleCircuit,
+        generator::{
+            downsize_srs, gen_pk, gen_proof, gen_sol_verifier, gen_srs, verify_proof, MultiOpen,
+            Transcript,
+        },
+    };
+
+    #[test]
+    #[ignore = "slow, aggregation needs k = 21"]
+    fn aggregate() {
+        let params = gen_srs(21).unwrap();
+        let params_app = downsize_srs(&params, 4).unwrap();
+
+        let constant = Fr::from(7);
+        let pk = gen_pk(
+            &params_app,
+            &SimpleCircuit {
+                constant,
+                a: Value::unknown(),
+                b: Value::unknown(),
+            },
+        )
+        .unwrap();
+        let snarks = [(3, 5), (2, 9)].map(|(a, b)| {
+            let (a, b) = (Fr::from(a), Fr::from(b));
+            let circuit = SimpleCircuit {
+                constant,
+                a: Value::known(a),
+                b: Value::known(b),
+            };
+            let instances = vec![vec![constant * a * a * b * b]];
+            gen_snark(&params_app, &pk, circuit, instances).unwrap()
+        });
+
+        let circuit = AggregationCircuit::new(&params, snarks).unwrap();
+        let instances = circuit.instances();
+        MockProver::run(21, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        let pk = gen_pk(&params, &circuit).unwrap();
+        let proof = gen_proof(
+            &params,
+            &pk,
+            circuit,
+            &instances,
+            MultiOpen::Gwc,
+            Transcript::Evm,
+        )
+        .unwrap();
+        assert!(verify_proof(
+            &params,
+            pk.get_vk(),
+            proof,
+            &instances,
+            MultiOpen::Gwc,
+            Transcript::Evm
+        )
+        .unwrap());
+
+        let sol_code = gen_sol_verifier(
+            &params,
+            pk.get_vk(),
+            AggregationCircuit::num_instance(),
+            MultiOpen::Gwc,
+            Some(AggregationCircuit::accumulator_indices()),
+        )
+        .unwrap();
+        assert!(!sol_code.is_empty());
+    }
+}
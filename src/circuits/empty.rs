@@ -1,39 +1,307 @@
-use halo2_curves::ff::Field;
+use halo2_curves::bn256::Fr;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Instance, Selector},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector, TableColumn},
+    poly::Rotation,
 };
+use serde::{Deserialize, Serialize};
+
+use super::{gadgets::Field, inputs::field, CircuitArgs, RegisteredCircuit};
+
+/// Values `0..TABLE_SIZE` of the lookup table, the witness must be one of them.
+pub const TABLE_SIZE: usize = 16;
 
 #[derive(Clone, Debug)]
-pub struct Config {
-    pub advice: Column<Advice>,
+pub struct EmptyConfig {
+    pub advice: Vec<Column<Advice>>,
     pub instance: Column<Instance>,
     pub selector: Selector,
+    pub lookup: Option<(Selector, TableColumn)>,
+}
+
+/// Runtime shape of an [`EmptyCircuit`], kept by [`Circuit::without_witnesses`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EmptyParams {
+    /// Rows of the region, the gate and the lookups are enabled on each of them. At least one.
+    pub rows: usize,
+    /// Number of instances, each a copy of the witness.
+    pub num_instance: usize,
 }
 
-#[derive(Default, Clone)]
-pub struct EmptyCircuit<F: Field> {
-    pub constant: F,
+/// Dummy circuit of a chosen shape, to measure how the verifier contract size, calldata size
+/// and verification gas scale with each dimension. It fills `ADVICE` advice columns with the
+/// witness `a`, constrains them by a gate of degree `DEGREE` and looks them up `LOOKUPS` times
+/// in a fixed table. The columns, degree and lookups are fixed by `configure`, so they are
+/// const parameters, while the rows and instances are [`EmptyParams`].
+#[derive(Clone, Debug)]
+pub struct EmptyCircuit<F: Field, const ADVICE: usize, const DEGREE: usize, const LOOKUPS: usize> {
+    pub params: EmptyParams,
     pub a: Value<F>,
 }
 
-impl<F: Field> Circuit<F> for EmptyCircuit<F> {
-    type Config = Config;
+impl<F: Field, const ADVICE: usize, const DEGREE: usize, const LOOKUPS: usize> Circuit<F>
+    for EmptyCircuit<F, ADVICE, DEGREE, LOOKUPS>
+{
+    type Config = EmptyConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            params: self.params,
+            a: Value::unknown(),
+        }
     }
 
-    fn configure(_meta: &mut ConstraintSystem<F>) -> Self::Config {
-        todo!()
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        assert!(ADVICE > 0, "empty circuit needs an advice column");
+        assert!(DEGREE >= 2, "empty circuit gate has at least degree 2");
+
+        let advice = (0..ADVICE)
+            .map(|_| meta.advice_column())
+            .collect::<Vec<_>>();
+        let instance = meta.instance_column();
+        let selector = meta.selector();
+        for column in &advice {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+
+        meta.create_gate("dummy gate", |meta| {
+            // two products of DEGREE - 1 cells, which are equal as all cells hold `a`
+            let mut product = |from: usize| {
+                (from..from + DEGREE - 1)
+                    .map(|idx| meta.query_advice(advice[idx % ADVICE], Rotation::cur()))
+                    .reduce(|product, cell| product * cell)
+                    .unwrap()
+            };
+            let lhs = product(0);
+            let rhs = product(1);
+            let s = meta.query_selector(selector);
+            vec![s * (lhs - rhs)]
+        });
+
+        let lookup = (LOOKUPS > 0).then(|| {
+            let selector = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            for idx in 0..LOOKUPS {
+                meta.lookup("dummy lookup", |meta| {
+                    let s = meta.query_selector(selector);
+                    let cell = meta.query_advice(advice[idx % ADVICE], Rotation::cur());
+                    vec![(s * cell, table)]
+                });
+            }
+            (selector, table)
+        });
+
+        EmptyConfig {
+            advice,
+            instance,
+            selector,
+            lookup,
+        }
     }
 
     fn synthesize(
         &self,
-        _config: Self::Config,
-        mut _layouter: impl Layouter<F>,
-    ) -> Result<(), halo2_proofs::plonk::Error> {
-        todo!()
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let EmptyParams { rows, num_instance } = self.params;
+
+        if let Some((_, table)) = config.lookup {
+            layouter.assign_table(
+                || "dummy table",
+                |mut region| {
+                    for value in 0..TABLE_SIZE {
+                        region.assign_cell(
+                            || "table value",
+                            table,
+                            value,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+
+        let cell = layouter.assign_region(
+            || "dummy region",
+            |mut region| {
+                let mut first = None;
+                for row in 0..rows {
+                    config.selector.enable(&mut region, row)?;
+                    if let Some((selector, _)) = config.lookup {
+                        selector.enable(&mut region, row)?;
+                    }
+                    for column in &config.advice {
+                        let cell = region.assign_advice(|| "a", *column, row, || self.a)?;
+                        first.get_or_insert(cell);
+                    }
+                }
+                first.ok_or(Error::Synthesis)
+            },
+        )?;
+
+        for row in 0..num_instance {
+            layouter.constrain_instance(cell.cell(), config.instance, row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Private input of [`EmptyCircuit`], it must be below [`TABLE_SIZE`] if there are lookups.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmptyInputs {
+    #[serde(with = "field")]
+    pub a: Fr,
+}
+
+impl Default for EmptyInputs {
+    fn default() -> Self {
+        EmptyInputs { a: Fr::from(1) }
+    }
+}
+
+impl<const ADVICE: usize, const DEGREE: usize, const LOOKUPS: usize> RegisteredCircuit
+    for EmptyCircuit<Fr, ADVICE, DEGREE, LOOKUPS>
+{
+    const NAME: &'static str = "empty";
+
+    type Inputs = EmptyInputs;
+
+    fn num_instance(&self) -> Vec<usize> {
+        vec![self.params.num_instance]
+    }
+
+    fn empty(args: &CircuitArgs) -> Result<Self, String> {
+        let rows = args.get_u64("rows", 8)? as usize;
+        if rows == 0 {
+            return Err(String::from("empty circuit needs at least one row"));
+        }
+        Ok(EmptyCircuit {
+            params: EmptyParams {
+                rows,
+                num_instance: args.get_u64("instances", 1)? as usize,
+            },
+            a: Value::unknown(),
+        })
+    }
+
+    fn witnessed(args: &CircuitArgs, inputs: EmptyInputs) -> Result<(Self, Vec<Vec<Fr>>), String> {
+        let circuit = EmptyCircuit {
+            a: Value::known(inputs.a),
+            ..Self::empty(args)?
+        };
+        let instances = vec![inputs.a; circuit.params.num_instance];
+
+        Ok((circuit, vec![instances]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::dev::MockProver;
+
+    use super::{EmptyCircuit, EmptyInputs};
+    use crate::{
+        circuits::{stats::CircuitStats, CircuitArgs, RegisteredCircuit},
+        generator::{
+            compile_solidity, evm_verify, gen_pk, gen_proof, gen_sol_verifier, gen_srs, EvmReport,
+            MultiOpen, Transcript,
+        },
+    };
+
+    fn witnessed<const ADVICE: usize, const DEGREE: usize, const LOOKUPS: usize>(
+        rows: u64,
+        num_instance: u64,
+        a: u64,
+    ) -> (EmptyCircuit<Fr, ADVICE, DEGREE, LOOKUPS>, Vec<Vec<Fr>>) {
+        let args = CircuitArgs::new()
+            .with("rows", Fr::from(rows))
+            .with("instances", Fr::from(num_instance));
+        EmptyCircuit::witnessed(&args, EmptyInputs { a: Fr::from(a) }).unwrap()
+    }
+
+    #[test]
+    fn shape() {
+        let (circuit, instances) = witnessed::<3, 6, 2>(20, 2, 5);
+        let stats = CircuitStats::measure(&circuit).unwrap();
+        assert_eq!(stats.advice_columns, 3);
+        assert_eq!(stats.max_degree, 6);
+        assert_eq!(stats.lookups, 2);
+        assert_eq!(stats.rows, 20);
+        MockProver::run(circuit.required_k(), &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+
+        // the witness must be in the lookup table
+        let (circuit, instances) = witnessed::<3, 6, 2>(20, 2, 16);
+        let prover = MockProver::run(circuit.required_k(), &circuit, instances).unwrap();
+        assert!(prover.verify().is_err());
+        let (circuit, instances) = witnessed::<1, 2, 0>(4, 1, 16);
+        MockProver::run(circuit.required_k(), &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+
+        let args = CircuitArgs::new().with("rows", Fr::from(0));
+        assert!(EmptyCircuit::<Fr, 1, 2, 0>::empty(&args).is_err());
+    }
+
+    /// Runs the verifier of a circuit of the given dimensions in the EVM.
+    fn evm_report<const ADVICE: usize, const DEGREE: usize, const LOOKUPS: usize>(
+        rows: u64,
+        num_instance: u64,
+    ) -> EvmReport {
+        let (circuit, instances) = witnessed::<ADVICE, DEGREE, LOOKUPS>(rows, num_instance, 1);
+        let params = gen_srs(circuit.required_k()).unwrap();
+        let pk = gen_pk(&params, &circuit).unwrap();
+        let proof = gen_proof(
+            &params,
+            &pk,
+            circuit.clone(),
+            &instances,
+            MultiOpen::Gwc,
+            Transcript::Evm,
+        )
+        .unwrap();
+        let code = gen_sol_verifier(
+            &params,
+            pk.get_vk(),
+            circuit.num_instance(),
+            MultiOpen::Gwc,
+            None,
+        )
+        .unwrap();
+        let report = evm_verify(compile_solidity(&code).unwrap(), &instances, &proof).unwrap();
+        assert!(report.success());
+        report
+    }
+
+    #[test]
+    #[ignore = "slow, needs solc"]
+    fn gas_scaling() {
+        let base = evm_report::<1, 4, 0>(8, 1);
+        let grows = |report: EvmReport| {
+            assert!(report.calldata_size > base.calldata_size);
+            assert!(report.code_size > base.code_size);
+            assert!(report.call_gas > base.call_gas);
+        };
+        // every advice column and lookup adds commitments and evaluations to the proof,
+        // a higher degree adds quotient chunks
+        grows(evm_report::<4, 4, 0>(8, 1));
+        grows(evm_report::<1, 8, 0>(8, 1));
+        grows(evm_report::<1, 4, 2>(8, 1));
+
+        // the proof size doesn't depend on the rows
+        let report = evm_report::<1, 4, 0>(1000, 1);
+        assert_eq!(report.calldata_size, base.calldata_size);
+
+        // instances are passed as one word each, besides the same proof
+        let report = evm_report::<1, 4, 0>(8, 16);
+        assert_eq!(report.calldata_size, base.calldata_size + 15 * 32);
+        assert!(report.call_gas > base.call_gas);
     }
 }
@@ -1,71 +1,94 @@
+use halo2_curves::bn256::Fr;
 use halo2_proofs::{
-    arithmetic::Field,
-    circuit::{Layouter, SimpleFloorPlanner},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Constraints, Error, Instance, Selector},
-    poly::Rotation,
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    gadgets::{
+        recurrence::{
+            linear_recurrence, LinearRecurrenceChip, LinearRecurrenceConfig,
+            LinearRecurrenceInstruction,
+        },
+        Field,
+    },
+    inputs::field,
+    CircuitArgs, RegisteredCircuit,
 };
-use std::marker::PhantomData;
 
 #[derive(Clone, Debug)]
 pub struct FibonacciConfig {
-    advice: [Column<Advice>; 2],
+    recurrence: LinearRecurrenceConfig,
 
     instance: Column<Instance>,
-
-    selector: Selector,
 }
 
-#[derive(Debug, Clone)]
-struct FibonacciChip<F: Field> {
-    config: FibonacciConfig,
-    _marker: PhantomData<F>,
+/// Shape of a [`FibonacciCircuit`]. It is kept by [`Circuit::without_witnesses`], so the
+/// keygen circuit lays out the same region as the proving circuit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FibonacciParams {
+    /// Number of steps, the circuit exposes the `n`-th term.
+    pub n: usize,
+    /// Rows of the region, each holding one term.
+    pub rows: usize,
+    /// Also exposes the terms between `f1` and the `n`-th term.
+    pub expose_terms: bool,
 }
 
-impl<F: Field> FibonacciChip<F> {
-    pub fn construct(config: FibonacciConfig) -> Self {
+impl FibonacciParams {
+    /// Rows of the largest domain, `k = 28` is the two-adicity of the BN254 scalar field.
+    pub const MAX_ROWS: usize = 1 << 28;
+
+    /// Shape with the fewest rows which compute `n` steps.
+    pub fn new(n: usize) -> Self {
         Self {
-            config,
-            _marker: PhantomData,
+            n,
+            rows: n.saturating_add(1).max(2),
+            expose_terms: false,
         }
     }
 
-    fn configure(
-        meta: &mut ConstraintSystem<F>,
-        advice: [Column<Advice>; 2],
-        instance: Column<Instance>,
-    ) -> FibonacciConfig {
-        meta.enable_equality(instance);
-        for column in &advice {
-            meta.enable_equality(*column);
+    /// Pads the region to `rows` rows, e.g. to keep `k` fixed over several `n`. Fails if
+    /// `rows` can't hold `n` steps or exceed [`Self::MAX_ROWS`].
+    pub fn with_rows(self, rows: usize) -> Result<Self, String> {
+        if rows <= self.n || rows < 2 {
+            return Err(format!(
+                "{} rows can't hold {} fibonacci steps",
+                rows, self.n
+            ));
+        }
+        if rows > Self::MAX_ROWS {
+            return Err(format!(
+                "{} rows exceed the largest domain of {} rows",
+                rows,
+                Self::MAX_ROWS
+            ));
         }
-        let selector = meta.selector();
-
-        meta.create_gate("fibonacci", |meta| {
-            // | ins   | a0     |   a1   | seletor|
-            // |-------|------- |------- |------- |
-            // |   a   | f(0)=a | f(1)=b |    1   |
-            // |   b   | f(2)=b | f(3)   |    1   |
-            // |  out  | f(4)   | f(5)   |    0   |
-            let s = meta.query_selector(selector);
-            let lc = meta.query_advice(advice[0], Rotation::cur());
-            let rc = meta.query_advice(advice[1], Rotation::cur());
-            let ln = meta.query_advice(advice[0], Rotation::next());
-            let rn = meta.query_advice(advice[1], Rotation::next());
-            Constraints::with_selector(s, vec![(lc + rc.clone() - ln.clone()), (rc + ln - rn)])
-        });
+        Ok(Self { rows, ..self })
+    }
 
-        FibonacciConfig {
-            advice,
-            selector,
-            instance,
+    pub fn with_exposed_terms(self, expose_terms: bool) -> Self {
+        Self {
+            expose_terms,
+            ..self
+        }
+    }
+
+    /// Number of public instances: `f0`, `f1`, the `n`-th term and the exposed terms.
+    pub fn num_instance(&self) -> usize {
+        match self.expose_terms {
+            true => 3 + self.n.saturating_sub(2),
+            false => 3,
         }
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct FibonacciCircuit<F: Field> {
-    pub n: usize,
-    pub _marker: PhantomData<F>,
+    pub params: FibonacciParams,
+    pub f0: Value<F>,
+    pub f1: Value<F>,
 }
 
 impl<F: Field> Circuit<F> for FibonacciCircuit<F> {
@@ -74,93 +97,262 @@ impl<F: Field> Circuit<F> for FibonacciCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self {
+            params: self.params,
+            f0: Value::unknown(),
+            f1: Value::unknown(),
+        }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice = [meta.advice_column(), meta.advice_column()];
+        let term = meta.advice_column();
+        let coefficients = vec![meta.fixed_column(), meta.fixed_column()];
         let instance = meta.instance_column();
+        meta.enable_equality(instance);
 
-        FibonacciChip::configure(meta, advice, instance)
+        FibonacciConfig {
+            recurrence: LinearRecurrenceChip::configure(meta, term, coefficients),
+            instance,
+        }
     }
 
     fn synthesize(
         &self,
         config: Self::Config,
-        mut layouter: impl halo2_proofs::circuit::Layouter<F>,
+        mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        let field_chip = FibonacciChip::<F>::construct(config);
-
-        let out = layouter.assign_region(
-            || "fibo region",
-            |mut region| {
-                let lhs = field_chip.config.advice[0];
-                let rhs = field_chip.config.advice[1];
-                let instance = field_chip.config.instance;
-                let s = field_chip.config.selector;
-
-                let mut prev_left =
-                    region.assign_advice_from_instance(|| "f0", instance, 0, lhs, 0)?;
-                let mut prev_right =
-                    region.assign_advice_from_instance(|| "f1", instance, 1, rhs, 0)?;
-
-                for i in 1..=self.n / 2 {
-                    s.enable(&mut region, i - 1)?;
-                    let value = prev_left.value().copied() + prev_right.value().copied();
-
-                    let cur_left = region.assign_advice(|| "f left", lhs, i, || value)?;
-                    let value = prev_right.value().copied() + cur_left.value().copied();
-                    let cur_right = region.assign_advice(|| "f right", rhs, i, || value)?;
-                    prev_left = cur_left;
-                    prev_right = cur_right;
-                }
-
-                if self.n % 2 == 0 {
-                    return Ok(prev_left);
-                }
-                Ok(prev_right)
-            },
+        // f(i + 2) = f(i) + f(i + 1)
+        let chip = LinearRecurrenceChip::construct(config.recurrence, vec![F::ONE, F::ONE]);
+        let FibonacciParams {
+            n,
+            rows,
+            expose_terms,
+        } = self.params;
+
+        let terms = chip.assign(
+            layouter.namespace(|| "fibo region"),
+            &[self.f0, self.f1],
+            rows,
         )?;
 
-        layouter
-            .namespace(|| "out")
-            .constrain_instance(out.cell(), field_chip.config.instance, 2)
+        let mut layouter = layouter.namespace(|| "out");
+        let instance = config.instance;
+        layouter.constrain_instance(terms[0].cell(), instance, 0)?;
+        layouter.constrain_instance(terms[1].cell(), instance, 1)?;
+        // the n-th term is past the region if `rows` was set by hand below `n + 1`
+        let out = terms.get(n).ok_or(Error::Synthesis)?;
+        layouter.constrain_instance(out.cell(), instance, 2)?;
+        if expose_terms && n > 2 {
+            for (row, term) in terms[2..n].iter().enumerate() {
+                layouter.constrain_instance(term.cell(), instance, 3 + row)?;
+            }
+        }
+        Ok(())
     }
 }
 
-pub fn fib(n: u64) -> u64 {
-    match n {
-        0 => 1,
-        1 => 1,
-        _ => fib(n - 1) + fib(n - 2),
+/// Initial terms of [`FibonacciCircuit`], the number of steps `n` and the `rows` of the
+/// region are circuit arguments.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FibonacciInputs {
+    #[serde(with = "field")]
+    pub f0: Fr,
+    #[serde(with = "field")]
+    pub f1: Fr,
+}
+
+impl Default for FibonacciInputs {
+    fn default() -> Self {
+        FibonacciInputs {
+            f0: Fr::from(1),
+            f1: Fr::from(1),
+        }
     }
 }
 
+impl RegisteredCircuit for FibonacciCircuit<Fr> {
+    const NAME: &'static str = "fibonacci";
+
+    type Inputs = FibonacciInputs;
+
+    fn num_instance(&self) -> Vec<usize> {
+        vec![self.params.num_instance()]
+    }
+
+    fn empty(args: &CircuitArgs) -> Result<Self, String> {
+        let params = FibonacciParams::new(args.get_u64("n", 10)? as usize);
+        let rows = args.get_u64("rows", params.rows as u64)? as usize;
+        let expose_terms = args.get_u64("expose", 0)? != 0;
+        Ok(FibonacciCircuit {
+            params: params.with_rows(rows)?.with_exposed_terms(expose_terms),
+            f0: Value::unknown(),
+            f1: Value::unknown(),
+        })
+    }
+
+    fn witnessed(
+        args: &CircuitArgs,
+        inputs: FibonacciInputs,
+    ) -> Result<(Self, Vec<Vec<Fr>>), String> {
+        let FibonacciInputs { f0, f1 } = inputs;
+        let circuit = FibonacciCircuit {
+            f0: Value::known(f0),
+            f1: Value::known(f1),
+            ..Self::empty(args)?
+        };
+
+        let FibonacciParams {
+            n, expose_terms, ..
+        } = circuit.params;
+        let terms = fib(f0, f1, n);
+        let mut instances = vec![f0, f1, terms[n]];
+        if expose_terms && n > 2 {
+            instances.extend(&terms[2..n]);
+        }
+
+        Ok((circuit, vec![instances]))
+    }
+}
+
+/// Terms `f(0)` to `f(n)` of the fibonacci sequence starting with `f0` and `f1`, computed in
+/// the field.
+pub fn fib<F: Field>(f0: F, f1: F, n: usize) -> Vec<F> {
+    linear_recurrence(&[F::ONE, F::ONE], &[f0, f1], n + 1)
+}
+
 #[cfg(test)]
 mod tests {
-    use std::marker::PhantomData;
-
-    use super::{fib, FibonacciCircuit};
-    use halo2_curves::bn256::Fr;
-    use halo2_proofs::dev::MockProver;
+    use super::{fib, FibonacciCircuit, FibonacciInputs, FibonacciParams};
+    use crate::{
+        circuits::{CircuitArgs, RegisteredCircuit},
+        generator::{gen_pk, gen_proof, gen_srs, verify_proof, MultiOpen, Transcript},
+    };
+    use halo2_curves::{bn256::Fr, ff::PrimeField};
+    use halo2_proofs::{circuit::Value, dev::MockProver};
 
     #[test]
     fn verify() {
-        let k = 4;
-
         let f0 = Fr::from(1);
         let f1 = Fr::from(1);
         let n = 11;
-        let out = Fr::from(fib(n));
+        let out = fib(f0, f1, n)[n];
+        assert_eq!(out, Fr::from(144));
 
         let circuit = FibonacciCircuit {
-            n: n as usize,
-            _marker: PhantomData,
+            params: FibonacciParams::new(n),
+            f0: Value::known(f0),
+            f1: Value::known(f1),
         };
+        let k = circuit.required_k();
 
         let public_inputs = vec![f0, f1, out];
 
         let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
         assert_eq!(prover.verify(), Ok(()));
+
+        // the terms are witnesses, not copied from the instances
+        let prover = MockProver::run(k, &circuit, vec![vec![f0, f0 + f1, out]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn exposed_terms() {
+        // past n = 92 the terms no longer fit in u64
+        let args = CircuitArgs::new()
+            .with("n", Fr::from(100))
+            .with("expose", Fr::from(1));
+        let inputs = FibonacciInputs {
+            f0: Fr::from(0),
+            f1: Fr::from(1),
+        };
+        let (circuit, instances) = FibonacciCircuit::witnessed(&args, inputs).unwrap();
+        assert_eq!(circuit.num_instance(), vec![101]);
+        assert_eq!(instances[0][2], Fr::from_u128(354224848179261915075));
+        assert_eq!(instances[0][3..6], [Fr::from(1), Fr::from(2), Fr::from(3)]);
+
+        let k = circuit.required_k();
+        let prover = MockProver::run(k, &circuit, instances.clone()).unwrap();
+        prover.assert_satisfied();
+
+        let mut tampered = instances;
+        tampered[0][50] += Fr::from(1);
+        let prover = MockProver::run(k, &circuit, tampered).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let inputs = || FibonacciInputs {
+            f0: Fr::from(2),
+            f1: Fr::from(3),
+        };
+        for n in [0, 1, 2, 7, 10, 21] {
+            // keys come from the circuit without witnesses, as keygen derives them
+            let args = CircuitArgs::new().with("n", Fr::from(n));
+            let empty_circuit = FibonacciCircuit::empty(&args).unwrap();
+            let params = gen_srs(empty_circuit.required_k()).unwrap();
+            let pk = gen_pk(&params, &empty_circuit).unwrap();
+
+            let (circuit, instances) = FibonacciCircuit::witnessed(&args, inputs()).unwrap();
+            let proof = gen_proof(
+                &params,
+                &pk,
+                circuit,
+                &instances,
+                MultiOpen::Gwc,
+                Transcript::Evm,
+            )
+            .unwrap();
+            assert!(verify_proof(
+                &params,
+                pk.get_vk(),
+                proof.clone(),
+                &instances,
+                MultiOpen::Gwc,
+                Transcript::Evm
+            )
+            .unwrap());
+
+            let mut tampered = instances.clone();
+            tampered[0][2] += Fr::from(1);
+            assert!(!verify_proof(
+                &params,
+                pk.get_vk(),
+                proof,
+                &tampered,
+                MultiOpen::Gwc,
+                Transcript::Evm
+            )
+            .unwrap_or(false));
+        }
+
+        // padding the region keeps k the same over different n
+        let args = |n| {
+            CircuitArgs::new()
+                .with("n", Fr::from(n))
+                .with("rows", Fr::from(32))
+        };
+        assert_eq!(
+            FibonacciCircuit::empty(&args(3)).unwrap().required_k(),
+            FibonacciCircuit::empty(&args(21)).unwrap().required_k()
+        );
+        let (circuit, instances) = FibonacciCircuit::witnessed(&args(3), inputs()).unwrap();
+        assert_eq!(
+            circuit.params,
+            FibonacciParams::new(3).with_rows(32).unwrap()
+        );
+        assert_eq!(instances[0][2], Fr::from(8));
+        MockProver::run(circuit.required_k(), &circuit, instances)
+            .unwrap()
+            .assert_satisfied();
+
+        // too few rows for n is an argument error
+        assert!(FibonacciCircuit::empty(&args(32)).is_err());
+        assert!(FibonacciParams::new(1).with_rows(1).is_err());
+        // so are shapes past the largest domain, without overflowing on huge n
+        let steps = |n| CircuitArgs::new().with("n", Fr::from(n));
+        assert!(FibonacciCircuit::empty(&steps(FibonacciParams::MAX_ROWS as u64)).is_err());
+        assert!(FibonacciCircuit::empty(&steps(u64::MAX)).is_err());
     }
 }
@@ -1,14 +1,20 @@
-use std::marker::PhantomData;
-
+use halo2_curves::bn256::Fr;
 use halo2_proofs::{
     circuit::{Layouter, SimpleFloorPlanner, Value},
-    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Expression, Instance, Selector},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
     poly::Rotation,
 };
 
-use super::gadgets::{
-    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstructin},
-    Field,
+use serde::{Deserialize, Serialize};
+
+use super::{
+    gadgets::{
+        is_equal::{IsEqualChip, IsEqualConfig, IsEqualInstruction},
+        select::{ConditionalSelectChip, ConditionalSelectConfig, ConditionalSelectInstruction},
+        Field,
+    },
+    inputs::field,
+    CircuitArgs, RegisteredCircuit,
 };
 
 #[derive(Clone, Debug)]
@@ -17,17 +23,23 @@ pub struct FunctionConfig<F: Field> {
     pub a: Column<Advice>,
     pub b: Column<Advice>,
     pub c: Column<Advice>,
-    pub a_equals_b: IsZeroConfig<F>,
+    pub a_equals_b: IsEqualConfig<F>,
+    pub output: ConditionalSelectConfig,
     pub instance: Column<Instance>,
 }
 
 /// prove: f(a, b, c) = if a == b {c} else {a - b}
-#[derive(Clone, Default)]
+#[derive(Clone, Debug)]
 pub struct FunctionCircuit<F: Field> {
-    a: u64,
-    b: u64,
-    c: u64,
-    _marker: PhantomData<F>,
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub c: Value<F>,
+}
+
+impl<F: Field> FunctionCircuit<F> {
+    pub fn new(a: Value<F>, b: Value<F>, c: Value<F>) -> Self {
+        FunctionCircuit { a, b, c }
+    }
 }
 
 impl<F: Field> Circuit<F> for FunctionCircuit<F> {
@@ -35,7 +47,7 @@ impl<F: Field> Circuit<F> for FunctionCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        FunctionCircuit::default()
+        FunctionCircuit::new(Value::unknown(), Value::unknown(), Value::unknown())
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -43,33 +55,30 @@ impl<F: Field> Circuit<F> for FunctionCircuit<F> {
         let a = meta.advice_column();
         let b = meta.advice_column();
         let c = meta.advice_column();
+        let output = meta.advice_column();
         let instance = meta.instance_column();
 
-        meta.enable_equality(a);
+        meta.enable_equality(output);
         meta.enable_equality(instance);
 
-        let is_zero_value_inv = meta.advice_column();
+        let is_equal_value_inv = meta.advice_column();
 
-        let a_equals_b = IsZeroChip::configure(
+        let a_equals_b = IsEqualChip::configure(
             meta,
             |meta| meta.query_selector(selector),
-            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
-            is_zero_value_inv,
+            |meta| meta.query_advice(a, Rotation::cur()),
+            |meta| meta.query_advice(b, Rotation::cur()),
+            is_equal_value_inv,
         );
 
-        meta.create_gate("function gate", |meta| {
-            let s = meta.query_selector(selector);
-            let ac = meta.query_advice(a, Rotation::cur());
-            let b = meta.query_advice(b, Rotation::cur());
-            let c = meta.query_advice(c, Rotation::cur());
-            let output = meta.query_advice(a, Rotation::next());
-
-            vec![
-                s.clone() * a_equals_b.clone().is_zero_expression.clone() * (output.clone() - c),
-                s * (Expression::Constant(F::ONE) - a_equals_b.clone().is_zero_expression)
-                    * (output - ac + b),
-            ]
-        });
+        let output = ConditionalSelectChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |_| a_equals_b.is_equal_expression.clone(),
+            |meta| meta.query_advice(c, Rotation::cur()),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            output,
+        );
 
         FunctionConfig {
             selector,
@@ -77,6 +86,7 @@ impl<F: Field> Circuit<F> for FunctionCircuit<F> {
             b,
             c,
             a_equals_b,
+            output,
             instance,
         }
     }
@@ -86,31 +96,23 @@ impl<F: Field> Circuit<F> for FunctionCircuit<F> {
         config: Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
-        let is_zero_chip = IsZeroChip::construct(config.a_equals_b);
+        let is_equal_chip = IsEqualChip::construct(config.a_equals_b);
+        let select_chip = ConditionalSelectChip::construct(config.output);
 
         let output = layouter.assign_region(
             || "function region",
             |mut region| {
                 config.selector.enable(&mut region, 0)?;
 
-                region.assign_advice(|| "load a", config.a, 0, || Value::known(F::from(self.a)))?;
-                region.assign_advice(|| "load b", config.b, 0, || Value::known(F::from(self.b)))?;
-                region.assign_advice(|| "load c", config.c, 0, || Value::known(F::from(self.c)))?;
-                let output = if self.a == self.b {
-                    F::from(self.c)
-                } else {
-                    F::from(self.a) - F::from(self.b)
-                };
-                let output =
-                    region.assign_advice(|| "output", config.a, 1, || Value::known(output))?;
-
-                is_zero_chip.assign(
-                    &mut region,
-                    0,
-                    Value::known(F::from(self.a) - F::from(self.b)),
-                )?;
-
-                Ok(output)
+                region.assign_advice(|| "load a", config.a, 0, || self.a)?;
+                region.assign_advice(|| "load b", config.b, 0, || self.b)?;
+                region.assign_advice(|| "load c", config.c, 0, || self.c)?;
+
+                is_equal_chip.assign(&mut region, 0, self.a, self.b)?;
+                let diff = self.a - self.b;
+                let a_equals_b = diff.map(|diff| F::from(bool::from(diff.is_zero()) as u64));
+
+                select_chip.assign(&mut region, 0, a_equals_b, self.c, diff)
             },
         )?;
 
@@ -118,34 +120,89 @@ impl<F: Field> Circuit<F> for FunctionCircuit<F> {
     }
 }
 
+/// Private inputs of [`FunctionCircuit`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FunctionInputs {
+    #[serde(with = "field")]
+    pub a: Fr,
+    #[serde(with = "field")]
+    pub b: Fr,
+    #[serde(with = "field")]
+    pub c: Fr,
+}
+
+impl Default for FunctionInputs {
+    fn default() -> Self {
+        FunctionInputs {
+            a: Fr::from(2),
+            b: Fr::from(10),
+            c: Fr::from(3),
+        }
+    }
+}
+
+impl RegisteredCircuit for FunctionCircuit<Fr> {
+    const NAME: &'static str = "function";
+
+    type Inputs = FunctionInputs;
+
+    fn num_instance(&self) -> Vec<usize> {
+        vec![1]
+    }
+
+    fn empty(_args: &CircuitArgs) -> Result<Self, String> {
+        Ok(FunctionCircuit::new(
+            Value::unknown(),
+            Value::unknown(),
+            Value::unknown(),
+        ))
+    }
+
+    fn witnessed(
+        _args: &CircuitArgs,
+        inputs: FunctionInputs,
+    ) -> Result<(Self, Vec<Vec<Fr>>), String> {
+        let FunctionInputs { a, b, c } = inputs;
+        let circuit = FunctionCircuit::new(Value::known(a), Value::known(b), Value::known(c));
+
+        Ok((circuit, vec![vec![function(a, b, c)]]))
+    }
+}
+
+/// Native reference of [`FunctionCircuit`].
+pub fn function<F: Field>(a: F, b: F, c: F) -> F {
+    if a == b {
+        c
+    } else {
+        a - b
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use halo2_curves::bn256::Fr;
-    use halo2_proofs::dev::MockProver;
-    use std::marker::PhantomData;
+    use halo2_proofs::{circuit::Value, dev::MockProver};
 
-    use super::FunctionCircuit;
+    use super::{function, FunctionCircuit};
 
     #[test]
     fn verify() {
-        let a = 2;
-        let b = 10;
-        let c = 3;
-        let circuit = FunctionCircuit::<Fr> {
-            a,
-            b,
-            c,
-            _marker: PhantomData,
-        };
-
-        let out = if a == b {
-            Fr::from(c)
-        } else {
-            Fr::from(a) - Fr::from(b)
-        };
-        let out = vec![out];
-
-        let prover = MockProver::run(4, &circuit, vec![out]).unwrap();
-        prover.assert_satisfied();
+        for (a, b, c) in [(2, 10, 3), (10, 10, 3), (10, 2, 0)] {
+            let (a, b, c) = (Fr::from(a), Fr::from(b), Fr::from(c));
+            let circuit = FunctionCircuit::new(Value::known(a), Value::known(b), Value::known(c));
+
+            let out = vec![function(a, b, c)];
+            let prover = MockProver::run(4, &circuit, vec![out]).unwrap();
+            prover.assert_satisfied();
+
+            // the other branch must not be accepted
+            let out = match a == b {
+                true => vec![a - b],
+                false => vec![c],
+            };
+            let prover = MockProver::run(4, &circuit, vec![out]).unwrap();
+            assert!(prover.verify().is_err());
+        }
     }
 }
@@ -0,0 +1,223 @@
+use halo2_proofs::{
+    circuit::{Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+};
+
+use super::{
+    is_zero::{IsZeroChip, IsZeroConfig, IsZeroInstructin},
+    Field,
+};
+
+pub trait IsEqualInstruction<F: Field> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<(), Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F> {
+    pub is_zero: IsZeroConfig<F>,
+    pub is_equal_expression: Expression<F>,
+}
+
+pub struct IsEqualChip<F: Field> {
+    config: IsEqualConfig<F>,
+}
+
+impl<F: Field> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        IsEqualChip { config }
+    }
+}
+
+impl<F: Field> IsEqualChip<F> {
+    /// `is_equal_expression` is one if `lhs` equals `rhs` and zero otherwise.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let is_zero =
+            IsZeroChip::configure(meta, q_enable, |meta| lhs(meta) - rhs(meta), value_inv);
+        let is_equal_expression = is_zero.is_zero_expression.clone();
+
+        IsEqualConfig::<F> {
+            is_zero,
+            is_equal_expression,
+        }
+    }
+}
+
+impl<F: Field> IsEqualInstruction<F> for IsEqualChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<(), Error> {
+        let config = self.config();
+
+        IsZeroChip::construct(config.is_zero.clone()).assign(region, offset, lhs - rhs)
+    }
+}
+
+impl<F: Field> Chip<F> for IsEqualChip<F> {
+    type Config = IsEqualConfig<F>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    use super::{Field, IsEqualChip, IsEqualConfig, IsEqualInstruction};
+
+    macro_rules! try_test_circuit {
+        ($values:expr, $checks:expr) => {{
+            let k = usize::BITS - $values.len().leading_zeros() + 2;
+            let circuit = TestCircuit::<Fr> {
+                values: Some($values),
+                checks: Some($checks),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied()
+        }};
+    }
+
+    macro_rules! try_test_circuit_error {
+        ($values:expr, $checks:expr) => {{
+            let k = usize::BITS - $values.len().leading_zeros() + 2;
+            let circuit = TestCircuit::<Fr> {
+                values: Some($values),
+                checks: Some($checks),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify_par().is_err());
+        }};
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        check: Column<Advice>,
+        is_equal: IsEqualConfig<F>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        values: Option<Vec<(u64, u64)>>,
+        checks: Option<Vec<bool>>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let lhs = meta.advice_column();
+            let rhs = meta.advice_column();
+            let check = meta.advice_column();
+            let value_inv = meta.advice_column();
+
+            let is_equal = IsEqualChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+                value_inv,
+            );
+
+            let config = Self::Config {
+                q_enable,
+                lhs,
+                rhs,
+                check,
+                is_equal,
+            };
+
+            meta.create_gate("check is equal", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+
+                let check = meta.query_advice(config.check, Rotation::cur());
+
+                vec![q_enable * (config.is_equal.is_equal_expression.clone() - check)]
+            });
+
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = IsEqualChip::construct(config.is_equal.clone());
+
+            let values = self.values.as_ref().ok_or(Error::Synthesis)?;
+            let checks = self.checks.as_ref().ok_or(Error::Synthesis)?;
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (idx, ((lhs, rhs), check)) in values.iter().zip(checks).enumerate() {
+                        let (lhs, rhs) = (Value::known(F::from(*lhs)), Value::known(F::from(*rhs)));
+                        region.assign_advice(|| "lhs", config.lhs, idx, || lhs)?;
+                        region.assign_advice(|| "rhs", config.rhs, idx, || rhs)?;
+                        region.assign_advice(
+                            || "check",
+                            config.check,
+                            idx,
+                            || Value::known(F::from(*check as u64)),
+                        )?;
+
+                        config.q_enable.enable(&mut region, idx)?;
+                        chip.assign(&mut region, idx, lhs, rhs)?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_circuit() {
+        try_test_circuit!(vec![(1, 2), (5, 5), (0, 0)], vec![false, true, true]);
+        try_test_circuit!(vec![(u64::MAX, u64::MAX - 1)], vec![false]);
+        try_test_circuit_error!(vec![(1, 2)], vec![true]);
+        try_test_circuit_error!(vec![(3, 3)], vec![false]);
+    }
+}
@@ -0,0 +1,430 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, TableColumn, VirtualCells},
+    poly::Rotation,
+};
+
+use super::Field;
+
+pub trait LessThanInstruction<F: Field> {
+    /// Assigns the comparison of `lhs` with `rhs` at `offset` and returns its boolean cell.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
+/// Comparison of `lhs` with `rhs` computed by a [`LessThanChip`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    /// `lhs < rhs`
+    LessThan,
+    /// `lhs <= rhs`, which is `!(rhs < lhs)`
+    LessEq,
+    /// `lhs > rhs`, which is `rhs < lhs`
+    Greater,
+    /// `lhs >= rhs`, which is `!(lhs < rhs)`
+    GreaterEq,
+}
+
+impl Comparison {
+    /// Whether the operands are swapped to compare them with `<`.
+    fn swapped(&self) -> bool {
+        matches!(self, Comparison::LessEq | Comparison::Greater)
+    }
+
+    /// Whether the result is the negation of `<`.
+    fn negated(&self) -> bool {
+        matches!(self, Comparison::LessEq | Comparison::GreaterEq)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LessThanConfig<F, const N: usize> {
+    pub comparison: Comparison,
+    /// Boolean result of the comparison.
+    pub out: Column<Advice>,
+    /// Little-endian bytes of `lhs - rhs`, plus `256^N` if `lhs < rhs`.
+    pub diff: [Column<Advice>; N],
+    pub range: F,
+    pub u8_table: TableColumn,
+}
+
+/// Compares values below `256^N` by decomposing their difference into `N` bytes, each looked
+/// up in a table of all bytes.
+pub struct LessThanChip<F: Field, const N: usize> {
+    config: LessThanConfig<F, N>,
+}
+
+impl<F: Field, const N: usize> LessThanChip<F, N> {
+    pub fn construct(config: LessThanConfig<F, N>) -> Self {
+        LessThanChip { config }
+    }
+
+    /// Fills the byte table, once for all chips sharing it.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        load_u8_table(layouter, self.config.u8_table)
+    }
+}
+
+impl<F: Field, const N: usize> LessThanChip<F, N> {
+    /// Constrains `out` to the `comparison` of `lhs` with `rhs`, which must both be below
+    /// `256^N`. `q_enable` is used in lookups, so its selectors must be complex selectors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `256^N` doesn't fit in the field.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F> + Clone,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        comparison: Comparison,
+        u8_table: TableColumn,
+    ) -> LessThanConfig<F, N> {
+        assert!(
+            N > 0 && N * 8 < F::NUM_BITS as usize,
+            "{} bytes don't fit in the field",
+            N
+        );
+        let out = meta.advice_column();
+        meta.enable_equality(out);
+        let diff = [(); N].map(|_| meta.advice_column());
+        let range = F::from(256).pow_vartime([N as u64]);
+
+        meta.create_gate("less than gate", |meta| {
+            let q_enable = q_enable.clone()(meta);
+            let (lhs, rhs) = (lhs(meta), rhs(meta));
+            let (lhs, rhs) = match comparison.swapped() {
+                true => (rhs, lhs),
+                false => (lhs, rhs),
+            };
+
+            let out = meta.query_advice(out, Rotation::cur());
+            let lt = match comparison.negated() {
+                true => Expression::Constant(F::ONE) - out.clone(),
+                false => out.clone(),
+            };
+            let diff = diff
+                .iter()
+                .rev()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .reduce(|diff, byte| diff * Expression::Constant(F::from(256)) + byte)
+                .unwrap();
+
+            [
+                q_enable.clone() * (lhs - rhs - diff + lt * Expression::Constant(range)),
+                q_enable * out.clone() * (Expression::Constant(F::ONE) - out),
+            ]
+        });
+
+        for column in diff {
+            meta.lookup("less than byte", |meta| {
+                let q_enable = q_enable.clone()(meta);
+                let byte = meta.query_advice(column, Rotation::cur());
+                vec![(q_enable * byte, u8_table)]
+            });
+        }
+
+        LessThanConfig {
+            comparison,
+            out,
+            diff,
+            range,
+            u8_table,
+        }
+    }
+}
+
+impl<F: Field, const N: usize> LessThanInstruction<F> for LessThanChip<F, N> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+        let (lhs, rhs) = match config.comparison.swapped() {
+            true => (rhs, lhs),
+            false => (lhs, rhs),
+        };
+
+        let lt = lhs.zip(rhs).map(|(lhs, rhs)| lhs < rhs);
+        let diff = lhs.zip(rhs).zip(lt).map(|((lhs, rhs), lt)| match lt {
+            true => lhs - rhs + config.range,
+            false => lhs - rhs,
+        });
+        let bytes = diff.map(|diff| diff.to_repr());
+        for (idx, column) in config.diff.iter().enumerate() {
+            region.assign_advice(
+                || format!("diff byte {}", idx),
+                *column,
+                offset,
+                || bytes.map(|bytes| F::from(bytes[idx] as u64)),
+            )?;
+        }
+
+        let negated = config.comparison.negated();
+        region.assign_advice(
+            || "comparison result",
+            config.out,
+            offset,
+            || lt.map(|lt| F::from((lt != negated) as u64)),
+        )
+    }
+}
+
+impl<F: Field, const N: usize> Chip<F> for LessThanChip<F, N> {
+    type Config = LessThanConfig<F, N>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// Fills `table` with all bytes.
+pub fn load_u8_table<F: Field>(
+    layouter: &mut impl Layouter<F>,
+    table: TableColumn,
+) -> Result<(), Error> {
+    layouter.assign_table(
+        || "u8 table",
+        |mut table_region| {
+            for byte in 0..256 {
+                table_region.assign_cell(
+                    || "byte",
+                    table,
+                    byte,
+                    || Value::known(F::from(byte as u64)),
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector},
+        poly::Rotation,
+    };
+
+    use super::{Comparison, Field, LessThanChip, LessThanConfig, LessThanInstruction};
+
+    const COMPARISONS: [Comparison; 4] = [
+        Comparison::LessThan,
+        Comparison::LessEq,
+        Comparison::Greater,
+        Comparison::GreaterEq,
+    ];
+
+    macro_rules! try_test_circuit {
+        ($values:expr, $checks:expr) => {{
+            let checks: Vec<[bool; 4]> = $checks;
+            let instances = instances(&checks);
+            let circuit = TestCircuit::<Fr> {
+                values: Some($values),
+                checks: Some(checks),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(9, &circuit, instances).unwrap();
+            prover.assert_satisfied()
+        }};
+    }
+
+    macro_rules! try_test_circuit_error {
+        ($values:expr, $checks:expr) => {{
+            let checks: Vec<[bool; 4]> = $checks;
+            let instances = instances(&checks);
+            let circuit = TestCircuit::<Fr> {
+                values: Some($values),
+                checks: Some(checks),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(9, &circuit, instances).unwrap();
+            assert!(prover.verify_par().is_err());
+        }};
+    }
+
+    /// Results of `<`, `<=`, `>` and `>=`.
+    fn compare(lhs: u64, rhs: u64) -> [bool; 4] {
+        [lhs < rhs, lhs <= rhs, lhs > rhs, lhs >= rhs]
+    }
+
+    /// Results of all rows exposed in one instance column.
+    fn instances(checks: &[[bool; 4]]) -> Vec<Vec<Fr>> {
+        vec![checks
+            .iter()
+            .flatten()
+            .map(|check| Fr::from(*check as u64))
+            .collect()]
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig<F> {
+        q_enable: Selector,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        checks: [Column<Advice>; 4],
+        comparisons: Vec<LessThanConfig<F, 2>>,
+        instance: Column<Instance>,
+    }
+
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        values: Option<Vec<(u64, u64)>>,
+        checks: Option<Vec<[bool; 4]>>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.complex_selector();
+            let lhs = meta.advice_column();
+            let rhs = meta.advice_column();
+            let checks = [(); 4].map(|_| meta.advice_column());
+            let u8_table = meta.lookup_table_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let comparisons = COMPARISONS
+                .iter()
+                .map(|comparison| {
+                    LessThanChip::configure(
+                        meta,
+                        |meta| meta.query_selector(q_enable),
+                        |meta| meta.query_advice(lhs, Rotation::cur()),
+                        |meta| meta.query_advice(rhs, Rotation::cur()),
+                        *comparison,
+                        u8_table,
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            meta.create_gate("check comparisons", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+
+                comparisons
+                    .iter()
+                    .zip(checks)
+                    .map(|(comparison, check)| {
+                        let out = meta.query_advice(comparison.out, Rotation::cur());
+                        let check = meta.query_advice(check, Rotation::cur());
+                        q_enable.clone() * (out - check)
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                lhs,
+                rhs,
+                checks,
+                comparisons,
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chips = config
+                .comparisons
+                .iter()
+                .map(|comparison| LessThanChip::construct(comparison.clone()))
+                .collect::<Vec<_>>();
+            chips[0].load(&mut layouter)?;
+
+            let values = self.values.as_ref().ok_or(Error::Synthesis)?;
+            let checks = self.checks.as_ref().ok_or(Error::Synthesis)?;
+
+            let results = layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    let mut results = Vec::new();
+                    for (idx, ((lhs, rhs), checks)) in values.iter().zip(checks).enumerate() {
+                        let (lhs, rhs) = (Value::known(F::from(*lhs)), Value::known(F::from(*rhs)));
+                        region.assign_advice(|| "lhs", config.lhs, idx, || lhs)?;
+                        region.assign_advice(|| "rhs", config.rhs, idx, || rhs)?;
+                        config.q_enable.enable(&mut region, idx)?;
+
+                        for ((chip, column), check) in chips.iter().zip(config.checks).zip(checks) {
+                            region.assign_advice(
+                                || "check",
+                                column,
+                                idx,
+                                || Value::known(F::from(*check as u64)),
+                            )?;
+                            results.push(chip.assign(&mut region, idx, lhs, rhs)?);
+                        }
+                    }
+
+                    Ok(results)
+                },
+            )?;
+
+            for (row, result) in results.iter().enumerate() {
+                layouter.constrain_instance(result.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_circuit() {
+        let values = vec![(1, 2), (2, 1), (7, 7), (0, 65535), (65535, 0), (256, 255)];
+        let checks = values
+            .iter()
+            .map(|(lhs, rhs)| compare(*lhs, *rhs))
+            .collect();
+        try_test_circuit!(values, checks);
+
+        try_test_circuit_error!(vec![(1, 2)], vec![compare(2, 1)]);
+        try_test_circuit_error!(vec![(7, 7)], vec![[true, true, false, true]]);
+        // values must be below 256^2
+        try_test_circuit_error!(vec![(65536, 0)], vec![compare(65536, 0)]);
+    }
+
+    #[test]
+    fn exposed_result() {
+        let circuit = TestCircuit::<Fr> {
+            values: Some(vec![(1, 2), (7, 7)]),
+            checks: Some(vec![compare(1, 2), compare(7, 7)]),
+            _marker: PhantomData,
+        };
+
+        let mut instances = instances(&[compare(1, 2), compare(7, 7)]);
+        MockProver::<Fr>::run(9, &circuit, instances.clone())
+            .unwrap()
+            .assert_satisfied();
+
+        // the results are copied to the instances, so a wrong claim fails
+        instances[0][4] = Fr::from(1);
+        let prover = MockProver::<Fr>::run(9, &circuit, instances).unwrap();
+        assert!(prover.verify_par().is_err());
+    }
+}
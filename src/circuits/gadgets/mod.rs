@@ -3,8 +3,86 @@ use halo2_proofs::halo2curves::{
     ff::{Field as Halo2Field, FromUniformBytes, PrimeField},
 };
 
+pub mod is_equal;
 pub mod is_zero;
+pub mod less_than;
+pub mod recurrence;
+pub mod select;
 
-pub trait Field: Halo2Field + PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord {}
+pub trait Field: Halo2Field + PrimeField<Repr = [u8; 32]> + FromUniformBytes<64> + Ord {
+    /// Parses a decimal or `0x` prefixed hex value, a leading `-` negates it modulo the field
+    /// order. Values which are not below the field order are rejected.
+    fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (radix, digits) = match digits.strip_prefix("0x") {
+            Some(digits) => (16, digits),
+            None => (10, digits),
+        };
+        if digits.is_empty() {
+            return Err(format!("{} is not a number", s));
+        }
+
+        // little-endian bytes, same as the field repr
+        let mut repr = [0u8; 32];
+        for c in digits.chars() {
+            let mut carry = c
+                .to_digit(radix)
+                .ok_or_else(|| format!("{} is not a number", s))?;
+            for byte in repr.iter_mut() {
+                let value = *byte as u32 * radix + carry;
+                *byte = value as u8;
+                carry = value >> 8;
+            }
+            if carry != 0 {
+                return Err(format!("{} is out of field range", s));
+            }
+        }
+        let value = Option::<Self>::from(Self::from_repr(repr))
+            .ok_or_else(|| format!("{} is out of field range", s))?;
+
+        Ok(if negative { -value } else { value })
+    }
+
+    /// Formats as `0x` prefixed big-endian hex, which [`Field::parse`] reads back.
+    fn to_hex(&self) -> String {
+        let mut repr = self.to_repr();
+        repr.reverse();
+        format!("0x{}", hex::encode(repr))
+    }
+}
 impl Field for Fr {}
 impl Field for Fq {}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::halo2curves::{
+        bn256::Fr,
+        ff::{Field as Halo2Field, PrimeField},
+    };
+
+    use super::Field;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Fr::parse("1575"), Ok(Fr::from(1575)));
+        assert_eq!(Fr::parse("0x627"), Ok(Fr::from(1575)));
+        assert_eq!(Fr::parse("-1"), Ok(-Fr::ONE));
+        assert_eq!(Fr::parse("-0x1"), Ok(-Fr::ONE));
+
+        let max = (-Fr::ONE).to_hex();
+        assert_eq!(Fr::parse(&max), Ok(-Fr::ONE));
+        assert!(Fr::parse(Fr::MODULUS).is_err());
+        assert!(Fr::parse(&format!("0x1{}", &max[2..])).is_err());
+        assert!(Fr::parse("").is_err());
+        assert!(Fr::parse("0x").is_err());
+        assert!(Fr::parse("12a").is_err());
+
+        for value in [Fr::ZERO, Fr::from(7), -Fr::from(7)] {
+            assert_eq!(Fr::parse(&value.to_hex()), Ok(value));
+        }
+    }
+}
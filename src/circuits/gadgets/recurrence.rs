@@ -0,0 +1,315 @@
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::Field;
+
+pub trait LinearRecurrenceInstruction<F: Field> {
+    /// Assigns the first `rows` terms of the recurrence, starting with the `initial` terms,
+    /// and returns their cells.
+    fn assign(
+        &self,
+        layouter: impl Layouter<F>,
+        initial: &[Value<F>],
+        rows: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
+}
+
+/// Config of a recurrence `f(i + d) = c_0 f(i) + ... + c_{d-1} f(i + d - 1)` of order `d`,
+/// one term per row, with the coefficients in `d` fixed columns.
+#[derive(Clone, Debug)]
+pub struct LinearRecurrenceConfig {
+    pub term: Column<Advice>,
+    pub coefficients: Vec<Column<Fixed>>,
+    pub selector: Selector,
+}
+
+pub struct LinearRecurrenceChip<F: Field> {
+    config: LinearRecurrenceConfig,
+    coefficients: Vec<F>,
+}
+
+impl<F: Field> LinearRecurrenceChip<F> {
+    /// # Panics
+    ///
+    /// Panics if the number of `coefficients` isn't the order of the config.
+    pub fn construct(config: LinearRecurrenceConfig, coefficients: Vec<F>) -> Self {
+        assert_eq!(
+            coefficients.len(),
+            config.coefficients.len(),
+            "linear recurrence of order {} needs as many coefficients",
+            config.coefficients.len()
+        );
+        LinearRecurrenceChip {
+            config,
+            coefficients,
+        }
+    }
+}
+
+impl<F: Field> LinearRecurrenceChip<F> {
+    /// Configures a recurrence whose order is the number of `coefficients` columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no coefficient column is given.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        term: Column<Advice>,
+        coefficients: Vec<Column<Fixed>>,
+    ) -> LinearRecurrenceConfig {
+        assert!(
+            !coefficients.is_empty(),
+            "linear recurrence needs at least one coefficient"
+        );
+        meta.enable_equality(term);
+        let selector = meta.selector();
+
+        meta.create_gate("linear recurrence", |meta| {
+            // | term     | c_0 .. c_{d-1}  | selector |
+            // |----------|-----------------|----------|
+            // | f(i)     | coefficients    |    1     |
+            // | ...      |                 |          |
+            // | f(i + d) |                 |          |
+            let s = meta.query_selector(selector);
+            let next = meta.query_advice(term, Rotation(coefficients.len() as i32));
+            let sum = coefficients
+                .iter()
+                .enumerate()
+                .map(|(idx, coefficient)| {
+                    meta.query_fixed(*coefficient, Rotation::cur())
+                        * meta.query_advice(term, Rotation(idx as i32))
+                })
+                .reduce(|sum, term| sum + term)
+                .unwrap();
+            Constraints::with_selector(s, vec![next - sum])
+        });
+
+        LinearRecurrenceConfig {
+            term,
+            coefficients,
+            selector,
+        }
+    }
+}
+
+impl<F: Field> LinearRecurrenceInstruction<F> for LinearRecurrenceChip<F> {
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        initial: &[Value<F>],
+        rows: usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = self.config();
+        let order = self.coefficients.len();
+        if initial.len() != order || rows < order {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "linear recurrence",
+            |mut region| {
+                let mut values = initial.to_vec();
+                let mut terms = Vec::with_capacity(rows);
+                for row in 0..rows {
+                    if row >= order {
+                        let value = values[row - order..]
+                            .iter()
+                            .zip(&self.coefficients)
+                            .fold(Value::known(F::ZERO), |sum, (term, coefficient)| {
+                                sum + term.map(|term| term * coefficient)
+                            });
+                        values.push(value);
+                    }
+                    terms.push(region.assign_advice(
+                        || "term",
+                        config.term,
+                        row,
+                        || values[row],
+                    )?);
+
+                    if row + order < rows {
+                        config.selector.enable(&mut region, row)?;
+                        for (column, coefficient) in
+                            config.coefficients.iter().zip(&self.coefficients)
+                        {
+                            region.assign_fixed(
+                                || "coefficient",
+                                *column,
+                                row,
+                                || Value::known(*coefficient),
+                            )?;
+                        }
+                    }
+                }
+
+                Ok(terms)
+            },
+        )
+    }
+}
+
+impl<F: Field> Chip<F> for LinearRecurrenceChip<F> {
+    type Config = LinearRecurrenceConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+/// Native reference of [`LinearRecurrenceChip`], the first `rows` terms of the recurrence with
+/// `coefficients` starting with the `initial` terms. Takes `O(rows * d)` field operations.
+///
+/// # Panics
+///
+/// Panics if the number of `initial` terms isn't the number of `coefficients`.
+pub fn linear_recurrence<F: Field>(coefficients: &[F], initial: &[F], rows: usize) -> Vec<F> {
+    assert_eq!(
+        initial.len(),
+        coefficients.len(),
+        "linear recurrence of order {} needs as many initial terms",
+        coefficients.len()
+    );
+    let mut terms = initial.to_vec();
+    terms.truncate(rows);
+    while terms.len() < rows {
+        let next = terms[terms.len() - coefficients.len()..]
+            .iter()
+            .zip(coefficients)
+            .fold(F::ZERO, |sum, (term, coefficient)| {
+                sum + *term * coefficient
+            });
+        terms.push(next);
+    }
+    terms
+}
+
+#[cfg(test)]
+mod test {
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::{
+            bn256::Fr,
+            ff::{Field as Halo2Field, PrimeField},
+        },
+        plonk::{Circuit, Column, ConstraintSystem, Error, Instance},
+    };
+    use rand::{rngs::OsRng, Rng};
+
+    use super::{
+        linear_recurrence, Field, LinearRecurrenceChip, LinearRecurrenceConfig,
+        LinearRecurrenceInstruction,
+    };
+
+    const MAX_ORDER: usize = 4;
+
+    macro_rules! try_test_circuit {
+        ($coefficients:expr, $initial:expr, $rows:expr) => {{
+            let terms = linear_recurrence(&$coefficients, &$initial, $rows);
+            let circuit = TestCircuit::<Fr> {
+                coefficients: $coefficients,
+                initial: $initial,
+                rows: $rows,
+            };
+            let prover = MockProver::<Fr>::run(7, &circuit, vec![terms.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            let mut tampered = terms;
+            *tampered.last_mut().unwrap() += Fr::ONE;
+            let prover = MockProver::<Fr>::run(7, &circuit, vec![tampered]).unwrap();
+            assert!(prover.verify_par().is_err());
+        }};
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        recurrence: LinearRecurrenceConfig,
+        instance: Column<Instance>,
+    }
+
+    #[derive(Clone, Default)]
+    struct TestCircuit<F: Field> {
+        coefficients: Vec<F>,
+        initial: Vec<F>,
+        rows: usize,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let term = meta.advice_column();
+            let coefficients = (0..MAX_ORDER).map(|_| meta.fixed_column()).collect();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            TestCircuitConfig {
+                recurrence: LinearRecurrenceChip::configure(meta, term, coefficients),
+                instance,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            // lower orders lead with zero coefficients and terms
+            let padding = MAX_ORDER - self.coefficients.len();
+            let mut coefficients = vec![F::ZERO; padding];
+            coefficients.extend(&self.coefficients);
+            let mut initial = vec![Value::known(F::ZERO); padding];
+            initial.extend(self.initial.iter().map(|term| Value::known(*term)));
+
+            let chip = LinearRecurrenceChip::construct(config.recurrence, coefficients);
+            let terms = chip.assign(
+                layouter.namespace(|| "recurrence"),
+                &initial,
+                self.rows + padding,
+            )?;
+            for (row, term) in terms[padding..].iter().enumerate() {
+                layouter.constrain_instance(term.cell(), config.instance, row)?;
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_circuit() {
+        // fibonacci numbers of any n, past the range of u64
+        let terms = linear_recurrence(&[Fr::ONE, Fr::ONE], &[Fr::ZERO, Fr::ONE], 101);
+        assert_eq!(terms[93], Fr::from(12200160415121876738));
+        assert_eq!(terms[100], Fr::from_u128(354224848179261915075));
+        try_test_circuit!(vec![Fr::ONE, Fr::ONE], vec![Fr::ONE, Fr::ONE], 100);
+
+        for _ in 0..8 {
+            let order = OsRng.gen_range(1..=MAX_ORDER);
+            let coefficients = (0..order).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+            let initial = (0..order).map(|_| Fr::random(OsRng)).collect::<Vec<_>>();
+            let rows = OsRng.gen_range(MAX_ORDER..100);
+
+            // the native terms follow the recurrence one step at a time
+            let terms = linear_recurrence(&coefficients, &initial, rows);
+            assert_eq!(&terms[..order], initial.as_slice());
+            for idx in order..rows {
+                let next = linear_recurrence(&coefficients, &terms[idx - order..idx], order + 1);
+                assert_eq!(next[order], terms[idx]);
+            }
+
+            try_test_circuit!(coefficients, initial, rows);
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    circuit::{AssignedCell, Chip, Region, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, VirtualCells},
+    poly::Rotation,
+};
+
+use super::Field;
+
+pub trait ConditionalSelectInstruction<F: Field> {
+    /// Assigns `if cond { when_true } else { when_false }` at `offset` and returns its cell.
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cond: Value<F>,
+        when_true: Value<F>,
+        when_false: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ConditionalSelectConfig {
+    pub output: Column<Advice>,
+}
+
+pub struct ConditionalSelectChip<F: Field> {
+    config: ConditionalSelectConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: Field> ConditionalSelectChip<F> {
+    pub fn construct(config: ConditionalSelectConfig) -> Self {
+        ConditionalSelectChip {
+            config,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F: Field> ConditionalSelectChip<F> {
+    /// Constrains `output` to `when_true` if `cond` is one and to `when_false` if it is zero.
+    /// `cond` must be boolean, e.g. the `is_zero_expression` of an
+    /// [`IsZeroConfig`](super::is_zero::IsZeroConfig) enabled on the same rows.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        cond: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        when_true: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        when_false: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        output: Column<Advice>,
+    ) -> ConditionalSelectConfig {
+        meta.create_gate("conditional select gate", |meta| {
+            let q_enable = q_enable(meta);
+
+            let cond = cond(meta);
+            let when_true = when_true(meta);
+            let when_false = when_false(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+
+            // output = cond * when_true + (1 - cond) * when_false
+            [q_enable * (output - when_false.clone() - cond * (when_true - when_false))]
+        });
+
+        ConditionalSelectConfig { output }
+    }
+}
+
+impl<F: Field> ConditionalSelectInstruction<F> for ConditionalSelectChip<F> {
+    fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        cond: Value<F>,
+        when_true: Value<F>,
+        when_false: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = self.config();
+
+        let output = cond
+            .zip(when_true)
+            .zip(when_false)
+            .map(|((cond, when_true), when_false)| when_false + cond * (when_true - when_false));
+        region.assign_advice(|| "selected value", config.output, offset, || output)
+    }
+}
+
+impl<F: Field> Chip<F> for ConditionalSelectChip<F> {
+    type Config = ConditionalSelectConfig;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        halo2curves::bn256::Fr,
+        plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+        poly::Rotation,
+    };
+
+    use super::{
+        ConditionalSelectChip, ConditionalSelectConfig, ConditionalSelectInstruction, Field,
+    };
+
+    macro_rules! try_test_circuit {
+        ($rows:expr) => {{
+            let circuit = TestCircuit::<Fr> {
+                rows: Some($rows),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(4, &circuit, vec![]).unwrap();
+            prover.assert_satisfied()
+        }};
+    }
+
+    macro_rules! try_test_circuit_error {
+        ($rows:expr) => {{
+            let circuit = TestCircuit::<Fr> {
+                rows: Some($rows),
+                _marker: PhantomData,
+            };
+            let prover = MockProver::<Fr>::run(4, &circuit, vec![]).unwrap();
+            assert!(prover.verify_par().is_err());
+        }};
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitConfig {
+        q_enable: Selector,
+        cond: Column<Advice>,
+        x: Column<Advice>,
+        y: Column<Advice>,
+        expected: Column<Advice>,
+        select: ConditionalSelectConfig,
+    }
+
+    /// Rows of `(cond, x, y, expected)`.
+    #[derive(Default)]
+    struct TestCircuit<F: Field> {
+        rows: Option<Vec<(u64, u64, u64, u64)>>,
+        _marker: PhantomData<F>,
+    }
+
+    impl<F: Field> Circuit<F> for TestCircuit<F> {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let q_enable = meta.selector();
+            let cond = meta.advice_column();
+            let x = meta.advice_column();
+            let y = meta.advice_column();
+            let expected = meta.advice_column();
+            let output = meta.advice_column();
+
+            let select = ConditionalSelectChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(cond, Rotation::cur()),
+                |meta| meta.query_advice(x, Rotation::cur()),
+                |meta| meta.query_advice(y, Rotation::cur()),
+                output,
+            );
+
+            meta.create_gate("check selected value", |meta| {
+                let q_enable = meta.query_selector(q_enable);
+                let output = meta.query_advice(output, Rotation::cur());
+                let expected = meta.query_advice(expected, Rotation::cur());
+
+                vec![q_enable * (output - expected)]
+            });
+
+            TestCircuitConfig {
+                q_enable,
+                cond,
+                x,
+                y,
+                expected,
+                select,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let chip = ConditionalSelectChip::construct(config.select.clone());
+            let rows = self.rows.as_ref().ok_or(Error::Synthesis)?;
+
+            layouter.assign_region(
+                || "witness",
+                |mut region| {
+                    for (offset, (cond, x, y, expected)) in rows.iter().enumerate() {
+                        config.q_enable.enable(&mut region, offset)?;
+                        let [cond, x, y, expected] =
+                            [*cond, *x, *y, *expected].map(|value| Value::known(F::from(value)));
+                        region.assign_advice(|| "cond", config.cond, offset, || cond)?;
+                        region.assign_advice(|| "x", config.x, offset, || x)?;
+                        region.assign_advice(|| "y", config.y, offset, || y)?;
+                        region.assign_advice(
+                            || "expected",
+                            config.expected,
+                            offset,
+                            || expected,
+                        )?;
+
+                        chip.assign(&mut region, offset, cond, x, y)?;
+                    }
+
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn test_circuit() {
+        try_test_circuit!(vec![(1, 3, 5, 3), (0, 3, 5, 5), (1, 7, 7, 7)]);
+        try_test_circuit_error!(vec![(1, 3, 5, 5)]);
+        try_test_circuit_error!(vec![(0, 3, 5, 3)]);
+    }
+}
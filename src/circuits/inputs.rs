@@ -0,0 +1,66 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use serde::de::DeserializeOwned;
+
+use crate::generator::Error;
+
+/// Reads the private inputs of a circuit from a JSON witness file. Fields missing from, or
+/// unknown to, the input schema of the circuit are rejected.
+pub fn read_inputs<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+/// Serde helper for a field element given as a JSON number or as a string accepted by
+/// [`Field::parse`](super::gadgets::Field::parse), written as `0x` prefixed hex.
+pub mod field {
+    use halo2_curves::bn256::Fr;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::circuits::gadgets::Field;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Number(u64),
+        String(String),
+    }
+
+    pub fn serialize<S: Serializer>(value: &Fr, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fr, D::Error> {
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(Fr::from(value)),
+            Repr::String(value) => Fr::parse(&value).map_err(D::Error::custom),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use halo2_curves::bn256::Fr;
+
+    use super::read_inputs;
+    use crate::circuits::simple::SimpleInputs;
+
+    #[test]
+    fn simple_inputs() {
+        let file = env::temp_dir().join("halo2-evm-verifier-test-inputs.json");
+
+        fs::write(&file, r#"{"a": 3, "b": "-0x05"}"#).unwrap();
+        let inputs: SimpleInputs = read_inputs(&file).unwrap();
+        assert_eq!(inputs.a, Fr::from(3));
+        assert_eq!(inputs.b, -Fr::from(5));
+
+        fs::write(&file, r#"{"a": 3}"#).unwrap();
+        assert!(read_inputs::<SimpleInputs>(&file).is_err());
+        fs::write(&file, r#"{"a": 3, "b": 5, "c": 1575}"#).unwrap();
+        assert!(read_inputs::<SimpleInputs>(&file).is_err());
+        fs::write(&file, r#"{"a": 3, "b": "five"}"#).unwrap();
+        assert!(read_inputs::<SimpleInputs>(&file).is_err());
+    }
+}
@@ -1,5 +1,159 @@
+use std::collections::BTreeMap;
+
+use halo2_curves::{bn256::Fr, ff::PrimeField};
+use halo2_proofs::plonk::Circuit;
+use serde::de::DeserializeOwned;
+
+use self::{
+    empty::EmptyCircuit, fibonacci::FibonacciCircuit, function::FunctionCircuit,
+    simple::SimpleCircuit, stats::CircuitStats,
+};
+
 pub mod empty;
 pub mod fibonacci;
 pub mod function;
 pub mod gadgets;
+pub mod inputs;
 pub mod simple;
+pub mod stats;
+
+/// Named arguments used to build a registered circuit, e.g. `constant=7`.
+#[derive(Clone, Debug, Default)]
+pub struct CircuitArgs(BTreeMap<String, Fr>);
+
+impl CircuitArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, name: &str, value: Fr) -> Self {
+        self.0.insert(name.to_string(), value);
+        self
+    }
+
+    /// Returns the argument `name`, or `default` when it was not given.
+    pub fn get(&self, name: &str, default: Fr) -> Fr {
+        self.0.get(name).copied().unwrap_or(default)
+    }
+
+    /// Returns the argument `name` as a count or size, or `default` when it was not given.
+    /// Fails if the argument doesn't fit in a `u64`, e.g. a negative value.
+    pub fn get_u64(&self, name: &str, default: u64) -> Result<u64, String> {
+        let value = match self.0.get(name) {
+            Some(value) => value.to_repr(),
+            None => return Ok(default),
+        };
+        if value[8..].iter().any(|byte| *byte != 0) {
+            return Err(format!("argument [{}] doesn't fit in u64", name));
+        }
+        Ok(u64::from_le_bytes(value[..8].try_into().unwrap()))
+    }
+}
+
+impl Extend<(String, Fr)> for CircuitArgs {
+    fn extend<I: IntoIterator<Item = (String, Fr)>>(&mut self, iter: I) {
+        self.0.extend(iter)
+    }
+}
+
+/// A circuit which can be selected by name from the command line.
+pub trait RegisteredCircuit: Circuit<Fr> + Clone {
+    /// Name used to select the circuit.
+    const NAME: &'static str;
+
+    /// Schema of the private inputs, read from a witness file by [`inputs::read_inputs`].
+    type Inputs: DeserializeOwned + Default;
+
+    /// Smallest `k` whose params can hold this circuit, measured by [`CircuitStats`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the circuit can't be laid out.
+    fn required_k(&self) -> u32 {
+        CircuitStats::measure(self)
+            .unwrap_or_else(|e| panic!("measure circuit [{}] error: {}", Self::NAME, e))
+            .min_k
+    }
+
+    /// Number of instances in each instance column.
+    fn num_instance(&self) -> Vec<usize>;
+
+    /// Circuit without witnesses, used for key generation. Fails on invalid `args`.
+    fn empty(args: &CircuitArgs) -> Result<Self, String>;
+
+    /// Circuit with witnesses and its expected public instances. Fails on invalid `args`.
+    fn witnessed(args: &CircuitArgs, inputs: Self::Inputs) -> Result<(Self, Vec<Vec<Fr>>), String>;
+}
+
+/// Operation run against a circuit selected at runtime.
+pub trait CircuitVisitor {
+    type Output;
+
+    fn visit<C: RegisteredCircuit>(self) -> Self::Output;
+}
+
+macro_rules! registry {
+    ($($circuit:ty),* $(,)?) => {
+        /// Names of all registered circuits.
+        pub fn circuit_names() -> Vec<&'static str> {
+            vec![$(<$circuit as RegisteredCircuit>::NAME),*]
+        }
+
+        /// Runs `visitor` against the circuit registered as `name`.
+        pub fn visit_circuit<V: CircuitVisitor>(name: &str, visitor: V) -> Option<V::Output> {
+            $(
+                if name == <$circuit as RegisteredCircuit>::NAME {
+                    return Some(visitor.visit::<$circuit>());
+                }
+            )*
+            None
+        }
+    };
+}
+
+// `empty` is registered with one shape, others need their own const parameters
+registry!(
+    SimpleCircuit<Fr>,
+    FibonacciCircuit<Fr>,
+    FunctionCircuit<Fr>,
+    EmptyCircuit<Fr, 2, 3, 1>,
+);
+
+#[cfg(test)]
+mod tests {
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::dev::MockProver;
+
+    use super::{circuit_names, visit_circuit, CircuitArgs, CircuitVisitor, RegisteredCircuit};
+
+    struct MockProve;
+
+    impl CircuitVisitor for MockProve {
+        type Output = ();
+
+        fn visit<C: RegisteredCircuit>(self) {
+            let (circuit, instances) =
+                C::witnessed(&CircuitArgs::new(), C::Inputs::default()).unwrap();
+            let prover = MockProver::run(circuit.required_k(), &circuit, instances).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn registered_circuits() {
+        for name in circuit_names() {
+            assert!(visit_circuit(name, MockProve).is_some());
+        }
+        assert!(visit_circuit("unknown", MockProve).is_none());
+    }
+
+    #[test]
+    fn args() {
+        let args = CircuitArgs::new()
+            .with("n", Fr::from(u64::MAX))
+            .with("rows", -Fr::from(1));
+        assert_eq!(args.get_u64("n", 10), Ok(u64::MAX));
+        assert_eq!(args.get_u64("expose", 0), Ok(0));
+        assert!(args.get_u64("rows", 8).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use std::marker::PhantomData;
 
+use halo2_curves::bn256::Fr;
 use halo2_proofs::{
     arithmetic::Field,
     circuit::{AssignedCell, Chip, Layouter, Region, SimpleFloorPlanner, Value},
@@ -7,6 +8,10 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use serde::{Deserialize, Serialize};
+
+use super::{inputs::field, CircuitArgs, RegisteredCircuit};
+
 // instructions
 trait NumericInstructions<F: Field>: Chip<F> {
     /// Variable representing a number.
@@ -285,6 +290,56 @@ impl<F: Field> Circuit<F> for SimpleCircuit<F> {
     }
 }
 
+/// Private inputs of [`SimpleCircuit`], the constant is a circuit argument.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimpleInputs {
+    #[serde(with = "field")]
+    pub a: Fr,
+    #[serde(with = "field")]
+    pub b: Fr,
+}
+
+impl Default for SimpleInputs {
+    fn default() -> Self {
+        SimpleInputs {
+            a: Fr::from(3),
+            b: Fr::from(5),
+        }
+    }
+}
+
+impl RegisteredCircuit for SimpleCircuit<Fr> {
+    const NAME: &'static str = "simple";
+
+    type Inputs = SimpleInputs;
+
+    fn num_instance(&self) -> Vec<usize> {
+        vec![1]
+    }
+
+    fn empty(args: &CircuitArgs) -> Result<Self, String> {
+        Ok(SimpleCircuit {
+            constant: args.get("constant", Fr::from(7)),
+            a: Value::unknown(),
+            b: Value::unknown(),
+        })
+    }
+
+    fn witnessed(args: &CircuitArgs, inputs: SimpleInputs) -> Result<(Self, Vec<Vec<Fr>>), String> {
+        let constant = args.get("constant", Fr::from(7));
+        let SimpleInputs { a, b } = inputs;
+        let c = constant * a.square() * b.square();
+
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        Ok((circuit, vec![vec![c]]))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SimpleCircuit;
@@ -0,0 +1,234 @@
+use std::fmt;
+
+use halo2_curves::bn256::Fr;
+use halo2_proofs::{
+    arithmetic::Field,
+    circuit::{FloorPlanner, Value},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+        Fixed, Instance, Selector,
+    },
+};
+
+/// Shape of a circuit, measured from its constraint system and a layout of its regions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub gates: usize,
+    /// Number of polynomial constraints over all gates.
+    pub constraints: usize,
+    pub max_degree: usize,
+    pub lookups: usize,
+    pub permutation_columns: usize,
+    /// Rows assigned by the circuit, not counting the blinding rows.
+    pub rows: usize,
+    /// Rows reserved for blinding factors and the last row.
+    pub blinding_rows: usize,
+    /// Smallest `k` whose `2^k` rows hold the assigned and the blinding rows.
+    pub min_k: u32,
+}
+
+impl CircuitStats {
+    /// Measures `circuit`, which may be without witnesses.
+    pub fn measure<C: Circuit<Fr>>(circuit: &C) -> Result<Self, Error> {
+        let mut cs = ConstraintSystem::default();
+        let config = C::configure(&mut cs);
+
+        let mut layout = RowCounter::default();
+        C::FloorPlanner::synthesize(&mut layout, circuit, config, cs.constants().clone())?;
+
+        let blinding_rows = cs.blinding_factors() + 1;
+        // halo2 needs at least three rows beside the blinding factors
+        let n = (layout.rows + blinding_rows).max(cs.blinding_factors() + 3);
+        Ok(Self {
+            advice_columns: cs.num_advice_columns(),
+            fixed_columns: cs.num_fixed_columns(),
+            instance_columns: cs.num_instance_columns(),
+            selectors: cs.num_selectors(),
+            gates: cs.gates().len(),
+            constraints: cs.gates().iter().map(|gate| gate.polynomials().len()).sum(),
+            max_degree: cs.degree(),
+            lookups: cs.lookups().len(),
+            permutation_columns: cs.permutation().get_columns().len(),
+            rows: layout.rows,
+            blinding_rows,
+            min_k: n.next_power_of_two().trailing_zeros(),
+        })
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "advice columns: {}", self.advice_columns)?;
+        writeln!(f, "fixed columns: {}", self.fixed_columns)?;
+        writeln!(f, "instance columns: {}", self.instance_columns)?;
+        writeln!(f, "selectors: {}", self.selectors)?;
+        writeln!(
+            f,
+            "gates: {} ({} constraints)",
+            self.gates, self.constraints
+        )?;
+        writeln!(f, "max degree: {}", self.max_degree)?;
+        writeln!(f, "lookups: {}", self.lookups)?;
+        writeln!(f, "permutation columns: {}", self.permutation_columns)?;
+        writeln!(f, "rows: {} (+{} blinding)", self.rows, self.blinding_rows)?;
+        write!(f, "min k: {}", self.min_k)
+    }
+}
+
+/// Layout target recording the highest row touched by any assignment.
+#[derive(Default)]
+struct RowCounter {
+    rows: usize,
+}
+
+impl RowCounter {
+    fn touch(&mut self, row: usize) {
+        self.rows = self.rows.max(row + 1);
+    }
+}
+
+impl<F: Field> Assignment<F> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Fixed>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.touch(row);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        _: Column<Any>,
+        left_row: usize,
+        _: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.touch(left_row);
+        self.touch(right_row);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::{circuit::Value, dev::MockProver};
+
+    use super::CircuitStats;
+    use crate::circuits::{
+        fibonacci::{FibonacciCircuit, FibonacciParams},
+        simple::SimpleCircuit,
+    };
+
+    #[test]
+    fn measure() {
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let stats = CircuitStats::measure(&circuit).unwrap();
+        assert_eq!(stats.instance_columns, 1);
+        assert!(stats.rows > 0);
+        MockProver::run(stats.min_k, &circuit, vec![vec![Fr::from(1575)]])
+            .unwrap()
+            .assert_satisfied();
+
+        let rows = |n| {
+            let circuit = FibonacciCircuit::<Fr> {
+                params: FibonacciParams::new(n),
+                f0: Value::unknown(),
+                f1: Value::unknown(),
+            };
+            CircuitStats::measure(&circuit).unwrap().rows
+        };
+        assert!(rows(100) > rows(10));
+        let circuit = FibonacciCircuit::<Fr> {
+            params: FibonacciParams::new(100),
+            f0: Value::unknown(),
+            f1: Value::unknown(),
+        };
+        assert!(CircuitStats::measure(&circuit).unwrap().min_k > 4);
+    }
+}
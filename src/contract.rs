@@ -0,0 +1,301 @@
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use halo2_curves::{
+    bn256::{Fr, G1Affine},
+    ff::PrimeField,
+};
+use halo2_proofs::plonk::VerifyingKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    circuits::{gadgets::Field, inputs::field},
+    generator::{gen_sol_wrapper, CompiledContract, Error, MultiOpen, SOLC_SETTINGS},
+    proof::{hex_bytes, ProofArtifact},
+};
+
+const HARDHAT_FORMAT: &str = "hh-sol-artifact-1";
+
+/// Deployment artifact of a generated verifier in the Hardhat artifact format, which Foundry
+/// reads as well, with the solc and circuit metadata appended.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifierArtifact {
+    #[serde(rename = "_format")]
+    pub format: String,
+    pub contract_name: String,
+    pub source_name: String,
+    pub abi: serde_json::Value,
+    #[serde(with = "hex_bytes")]
+    pub bytecode: Vec<u8>,
+    #[serde(with = "hex_bytes")]
+    pub deployed_bytecode: Vec<u8>,
+    pub link_references: serde_json::Value,
+    pub deployed_link_references: serde_json::Value,
+    pub solc: SolcMetadata,
+    pub circuit: CircuitMetadata,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SolcMetadata {
+    pub version: String,
+    pub settings: serde_json::Value,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitMetadata {
+    pub name: String,
+    pub k: u32,
+    pub multi_open: MultiOpen,
+    /// Transcript hash of the verifying key the verifier was generated from.
+    #[serde(with = "field")]
+    pub vk_hash: Fr,
+    /// Number of instances in each instance column.
+    pub num_instance: Vec<usize>,
+}
+
+impl VerifierArtifact {
+    pub fn new(
+        contract: CompiledContract,
+        source_name: &str,
+        circuit: &str,
+        vk: &VerifyingKey<G1Affine>,
+        multi_open: MultiOpen,
+        num_instance: Vec<usize>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            format: HARDHAT_FORMAT.to_string(),
+            contract_name: contract.name,
+            source_name: source_name.to_string(),
+            abi: contract.abi,
+            bytecode: contract.bytecode,
+            deployed_bytecode: contract.deployed_bytecode,
+            link_references: serde_json::json!({}),
+            deployed_link_references: serde_json::json!({}),
+            solc: SolcMetadata {
+                version: contract.solc_version,
+                settings: serde_json::from_str(SOLC_SETTINGS)?,
+            },
+            circuit: CircuitMetadata {
+                name: circuit.to_string(),
+                k: vk.get_domain().k(),
+                multi_open,
+                vk_hash: vk.transcript_repr(),
+                num_instance,
+            },
+        })
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        Ok(writer.flush()?)
+    }
+}
+
+/// Writes a Foundry project into `dir` with the generated verifier `verifier_code`, the typed
+/// wrapper and a `forge` test which checks that `proof` is accepted and that a tampered copy of
+/// it reverts. The proof must use the EVM transcript and the verifier's multi-open scheme.
+pub fn write_foundry_project(
+    dir: impl AsRef<Path>,
+    verifier_code: &str,
+    proof: &ProofArtifact,
+) -> Result<(), Error> {
+    let dir = dir.as_ref();
+    let name = contract_name(verifier_code)
+        .ok_or_else(|| Error::Solc(String::from("no contract in verifier code")))?;
+    let num_instance = proof.instances.iter().map(Vec::len).collect::<Vec<_>>();
+
+    fs::create_dir_all(dir.join("src"))?;
+    fs::create_dir_all(dir.join("test"))?;
+    fs::write(
+        dir.join("foundry.toml"),
+        "[profile.default]\nsrc = \"src\"\ntest = \"test\"\nout = \"out\"\n",
+    )?;
+    fs::write(dir.join("src").join("Verifier.sol"), verifier_code)?;
+    fs::write(
+        dir.join("src").join("VerifierWrapper.sol"),
+        gen_sol_wrapper(&num_instance),
+    )?;
+    fs::write(
+        dir.join("test").join("Verifier.t.sol"),
+        gen_forge_test(name, proof),
+    )?;
+    Ok(())
+}
+
+/// Name of the first contract declared in solidity `code`.
+pub fn contract_name(code: &str) -> Option<&str> {
+    let mut tokens = code.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'));
+    tokens.find(|token| *token == "contract")?;
+    tokens.find(|token| !token.is_empty())
+}
+
+fn gen_forge_test(name: &str, proof: &ProofArtifact) -> String {
+    let instances = proof.instances.iter().flatten().collect::<Vec<_>>();
+    let assign_instances = instances
+        .iter()
+        .enumerate()
+        .map(|(idx, instance)| format!("        values[{}] = {};\n", idx, instance.to_hex()))
+        .collect::<String>();
+
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+import "../src/Verifier.sol";
+import "../src/VerifierWrapper.sol";
+
+// Generated for circuit `{circuit}` (k = {k}, {multi_open}), vk hash {vk_hash}.
+contract VerifierTest {{
+    bytes constant PROOF = hex"{proof}";
+
+    address verifier;
+    VerifierWrapper wrapper;
+
+    function setUp() public {{
+        verifier = address(new {name}());
+        wrapper = new VerifierWrapper(verifier);
+    }}
+
+    function instances() internal pure returns (uint256[] memory values) {{
+        values = new uint256[]({num_instance});
+{assign_instances}    }}
+
+    function testAcceptsProof() public view {{
+        require(wrapper.verify(instances(), PROOF), "proof rejected");
+    }}
+
+    function testRejectsTamperedProof() public {{
+        bytes memory proof = PROOF;
+        proof[0] = bytes1(uint8(proof[0]) ^ 1);
+
+        (bool success, ) = verifier.call(abi.encodePacked(instances(), proof));
+        require(!success, "tampered proof did not revert");
+        require(!wrapper.verify(instances(), proof), "tampered proof accepted");
+    }}
+
+    function testRejectsTamperedInstances() public view {{
+        uint256[] memory values = instances();
+        values[0] = addmod(values[0], 1, {modulus});
+        require(!wrapper.verify(values, PROOF), "tampered instances accepted");
+    }}
+}}
+"#,
+        circuit = proof.circuit,
+        k = proof.k,
+        multi_open = proof.multi_open,
+        vk_hash = proof.vk_hash.to_hex(),
+        proof = hex::encode(&proof.proof),
+        name = name,
+        num_instance = instances.len(),
+        assign_instances = assign_instances,
+        modulus = Fr::MODULUS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::circuit::Value;
+    use serde_json::json;
+
+    use super::{write_foundry_project, VerifierArtifact};
+    use crate::{
+        circuits::{gadgets::Field, simple::SimpleCircuit},
+        generator::{gen_pk, gen_proof, gen_srs, CompiledContract, MultiOpen, Transcript},
+        proof::ProofArtifact,
+    };
+
+    #[test]
+    fn hardhat_format() {
+        let params = gen_srs(4).unwrap();
+        let pk = gen_pk(
+            &params,
+            &SimpleCircuit {
+                constant: Fr::from(7),
+                a: Value::unknown(),
+                b: Value::unknown(),
+            },
+        )
+        .unwrap();
+        let contract = CompiledContract {
+            name: String::from("Halo2Verifier"),
+            abi: json!([{"stateMutability": "payable", "type": "fallback"}]),
+            bytecode: vec![0x60, 0x80],
+            deployed_bytecode: vec![0x60],
+            solc_version: String::from("0.8.19+commit.7dd6d404"),
+        };
+        let artifact = VerifierArtifact::new(
+            contract,
+            "Verifier.sol",
+            "simple",
+            pk.get_vk(),
+            MultiOpen::Gwc,
+            vec![1],
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&artifact).unwrap();
+        assert_eq!(value["_format"], "hh-sol-artifact-1");
+        assert_eq!(value["contractName"], "Halo2Verifier");
+        assert_eq!(value["bytecode"], "0x6080");
+        assert_eq!(value["deployedBytecode"], "0x60");
+        assert_eq!(value["circuit"]["k"], 4);
+        assert_eq!(value["circuit"]["numInstance"], json!([1]));
+        assert_eq!(
+            serde_json::from_value::<VerifierArtifact>(value).unwrap(),
+            artifact
+        );
+    }
+
+    #[test]
+    fn foundry_project() {
+        let params = gen_srs(4).unwrap();
+        let constant = Fr::from(7);
+        let pk = gen_pk(
+            &params,
+            &SimpleCircuit {
+                constant,
+                a: Value::unknown(),
+                b: Value::unknown(),
+            },
+        )
+        .unwrap();
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        let instances = vec![vec![Fr::from(1575)]];
+        let (multi_open, transcript) = (MultiOpen::Gwc, Transcript::Evm);
+        let proof = gen_proof(&params, &pk, circuit, &instances, multi_open, transcript).unwrap();
+        let proof = ProofArtifact::new(
+            "simple",
+            pk.get_vk(),
+            multi_open,
+            transcript,
+            instances,
+            proof,
+        );
+
+        let dir = env::temp_dir().join("halo2-evm-verifier-test-foundry");
+        let verifier_code = "// SPDX-License-Identifier: MIT\ncontract Halo2Verifier {}\n";
+        write_foundry_project(&dir, verifier_code, &proof).unwrap();
+
+        let test = fs::read_to_string(dir.join("test").join("Verifier.t.sol")).unwrap();
+        assert!(test.contains("new Halo2Verifier()"));
+        assert!(test.contains(&format!("values[0] = {};", Fr::from(1575).to_hex())));
+        assert!(test.contains(&hex::encode(&proof.proof)));
+        let wrapper = fs::read_to_string(dir.join("src").join("VerifierWrapper.sol")).unwrap();
+        assert!(wrapper.contains("NUM_INSTANCE = 1;"));
+        assert!(dir.join("foundry.toml").exists());
+        assert!(write_foundry_project(&dir, "", &proof).is_err());
+    }
+}
@@ -1,74 +1,582 @@
-use std::rc::Rc;
+use std::{
+    error, fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+    process::{Command, Stdio},
+    rc::Rc,
+};
 
-use halo2_curves::bn256::{Bn256, Fq, Fr, G1Affine};
+use clap::ValueEnum;
+use halo2_curves::{
+    bn256::{Bn256, Fq, Fr, G1Affine},
+    ff::PrimeField,
+};
 use halo2_proofs::{
-    plonk::{self, create_proof, keygen_pk, keygen_vk, Circuit, ProvingKey},
+    dev::{MockProver, VerifyFailure},
+    plonk::{self, create_proof, keygen_pk, keygen_vk, Circuit, ProvingKey, VerifyingKey},
     poly::{
-        commitment::ParamsProver,
+        commitment::{Params, ParamsProver},
         kzg::{
             commitment::{KZGCommitmentScheme, ParamsKZG},
-            multiopen::{ProverGWC, VerifierGWC},
+            multiopen::{ProverGWC, ProverSHPLONK, VerifierGWC, VerifierSHPLONK},
             strategy::AccumulatorStrategy,
         },
         VerificationStrategy,
     },
-    transcript::{TranscriptReadBuffer, TranscriptWriterBuffer},
+    transcript::{
+        Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptReadBuffer,
+        TranscriptWriterBuffer,
+    },
+    SerdeFormat,
 };
 use itertools::Itertools;
 use rand::rngs::OsRng;
+use revm::{
+    primitives::{CreateScheme, EVMError, ExecutionResult, Output, TransactTo, TxEnv},
+    InMemoryDB, EVM,
+};
+use serde::{Deserialize, Serialize};
 use snark_verifier::{
-    loader::evm::EvmLoader,
-    pcs::kzg::{Gwc19, KzgAs},
+    loader::{
+        evm::{encode_calldata, EvmLoader},
+        native::NativeLoader,
+    },
+    pcs::kzg::{Bdfg21, Gwc19, KzgAs, KzgDecidingKey, LimbsEncoding},
     system::halo2::{compile, transcript::evm::EvmTranscript, Config},
-    verifier::{self, SnarkVerifier},
+    verifier::{self, plonk::PlonkProtocol, SnarkVerifier},
 };
 
+use crate::aggregation::{PoseidonTranscript, BITS, LIMBS};
+
 #[derive(Debug)]
 pub enum Error {
-    Proof(String),
-    Verify(String),
+    /// Key generation, proving or verification error from halo2.
+    Plonk(plonk::Error),
+    /// File system error.
+    Io(io::Error),
+    /// Malformed key or params bytes.
+    Serde(io::Error),
+    /// Error from snark-verifier while building the verifier.
+    SnarkVerifier(SnarkVerifierError),
+    /// `solc` could not be run or rejected the generated contract.
+    Solc(String),
+    /// `k` is not supported by the scalar field or the given params.
+    InvalidK(u32),
+    /// Malformed or inconsistent trusted setup file.
+    Srs(String),
+    /// Malformed JSON file.
+    Json(serde_json::Error),
+    /// File written by an unsupported version of this crate.
+    UnsupportedVersion(u32),
+    /// Value that can't be parsed as a field element.
+    InvalidField(String),
+    /// The in-process EVM failed to execute a transaction.
+    Evm(String),
+    /// Calldata which doesn't match the instances of the circuit.
+    InvalidCalldata(String),
+    /// Malformed hex in a proof or calldata file.
+    Hex(hex::FromHexError),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Plonk(e) => write!(f, "plonk error: {}", e),
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Serde(e) => write!(f, "serde error: {}", e),
+            Error::SnarkVerifier(e) => write!(f, "snark verifier error: {}", e),
+            Error::Solc(e) => write!(f, "solc error: {}", e),
+            Error::InvalidK(k) => write!(f, "invalid k: {}", k),
+            Error::Srs(e) => write!(f, "srs error: {}", e),
+            Error::Json(e) => write!(f, "json error: {}", e),
+            Error::UnsupportedVersion(version) => write!(f, "unsupported version: {}", version),
+            Error::InvalidField(e) => write!(f, "invalid field element: {}", e),
+            Error::Evm(e) => write!(f, "evm error: {}", e),
+            Error::InvalidCalldata(e) => write!(f, "invalid calldata: {}", e),
+            Error::Hex(e) => write!(f, "hex error: {}", e),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Plonk(e) => Some(e),
+            Error::Io(e) | Error::Serde(e) => Some(e),
+            Error::Json(e) => Some(e),
+            Error::SnarkVerifier(e) => Some(e),
+            Error::Hex(e) => Some(e),
+            Error::Solc(_)
+            | Error::InvalidK(_)
+            | Error::Srs(_)
+            | Error::UnsupportedVersion(_)
+            | Error::InvalidField(_)
+            | Error::Evm(_)
+            | Error::InvalidCalldata(_) => None,
+        }
+    }
+}
+
+impl From<plonk::Error> for Error {
+    fn from(e: plonk::Error) -> Self {
+        Error::Plonk(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+impl From<snark_verifier::Error> for Error {
+    fn from(e: snark_verifier::Error) -> Self {
+        Error::SnarkVerifier(SnarkVerifierError(e))
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Error::Hex(e)
+    }
+}
+
+/// Error of snark-verifier, which doesn't implement [`error::Error`] itself.
+#[derive(Debug)]
+pub struct SnarkVerifierError(pub snark_verifier::Error);
+
+impl fmt::Display for SnarkVerifierError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl error::Error for SnarkVerifierError {}
+
+/// KZG multi-open scheme, proofs must be verified with the scheme they were created with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiOpen {
+    /// GWC19, the original PLONK multi-open argument.
+    #[default]
+    Gwc,
+    /// BDFG21 (SHPLONK), smaller proofs for circuits with many rotations.
+    Shplonk,
+}
+
+impl fmt::Display for MultiOpen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MultiOpen::Gwc => write!(f, "gwc"),
+            MultiOpen::Shplonk => write!(f, "shplonk"),
+        }
+    }
+}
+
+/// Fiat-Shamir transcript hash, proofs must be verified with the transcript they were created
+/// with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transcript {
+    /// Keccak256, the only transcript the solidity verifier can read.
+    #[default]
+    Evm,
+    /// Poseidon, cheap to verify in circuit, used by [`crate::aggregation`].
+    Poseidon,
+    /// Blake2b, fast to verify natively.
+    Blake2b,
 }
 
-type PlonkVerifier = verifier::plonk::PlonkVerifier<KzgAs<Bn256, Gwc19>>;
+impl fmt::Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transcript::Evm => write!(f, "evm"),
+            Transcript::Poseidon => write!(f, "poseidon"),
+            Transcript::Blake2b => write!(f, "blake2b"),
+        }
+    }
+}
+
+type PlonkVerifier<S, AE = ()> = verifier::plonk::PlonkVerifier<KzgAs<Bn256, S>, AE>;
+type Limbs = LimbsEncoding<LIMBS, BITS>;
+
+pub fn gen_srs(k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+    if k == 0 || k > Fr::S {
+        return Err(Error::InvalidK(k));
+    }
+    Ok(ParamsKZG::<Bn256>::setup(k, OsRng))
+}
+
+/// Derives params of a smaller `k` from `params`, which must be at least `k`.
+pub fn downsize_srs(params: &ParamsKZG<Bn256>, k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+    if k == 0 || k > params.k() {
+        return Err(Error::InvalidK(k));
+    }
+    let mut params = params.clone();
+    params.downsize(k);
+    Ok(params)
+}
+
+pub fn read_params(path: impl AsRef<Path>) -> Result<ParamsKZG<Bn256>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    ParamsKZG::<Bn256>::read(&mut reader).map_err(Error::Serde)
+}
+
+pub fn write_params(params: &ParamsKZG<Bn256>, path: impl AsRef<Path>) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    params.write(&mut writer)?;
+    Ok(writer.flush()?)
+}
+
+pub fn gen_vk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<VerifyingKey<G1Affine>, Error> {
+    Ok(keygen_vk(params, circuit)?)
+}
+
+pub fn gen_pk<C: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    circuit: &C,
+) -> Result<ProvingKey<G1Affine>, Error> {
+    let vk = gen_vk(params, circuit)?;
+    Ok(keygen_pk(params, vk, circuit)?)
+}
+
+pub fn write_vk(
+    vk: &VerifyingKey<G1Affine>,
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    vk.write(&mut writer, format)?;
+    Ok(writer.flush()?)
+}
 
-pub fn gen_srs(k: u32) -> ParamsKZG<Bn256> {
-    ParamsKZG::<Bn256>::setup(k, OsRng)
+/// Reads a verifying key written by [`write_vk`], `C` must be the circuit it was generated from.
+pub fn read_vk<C: Circuit<Fr>>(
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+) -> Result<VerifyingKey<G1Affine>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    VerifyingKey::read::<_, C>(&mut reader, format).map_err(Error::Serde)
 }
 
-pub fn gen_pk<C: Circuit<Fr>>(params: &ParamsKZG<Bn256>, circuit: &C) -> ProvingKey<G1Affine> {
-    let vk = keygen_vk(params, circuit).unwrap();
-    keygen_pk(params, vk, circuit).unwrap()
+pub fn write_pk(
+    pk: &ProvingKey<G1Affine>,
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+) -> Result<(), Error> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    pk.write(&mut writer, format)?;
+    Ok(writer.flush()?)
 }
 
-pub fn gen_sol_verifier<C: Circuit<Fr>>(
+/// Reads a proving key written by [`write_pk`], `C` must be the circuit it was generated from.
+pub fn read_pk<C: Circuit<Fr>>(
+    path: impl AsRef<Path>,
+    format: SerdeFormat,
+) -> Result<ProvingKey<G1Affine>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+    ProvingKey::read::<_, C>(&mut reader, format).map_err(Error::Serde)
+}
+
+/// Generates the solidity verifier of `vk`.
+///
+/// For an [`AggregationCircuit`](crate::aggregation::AggregationCircuit), `accumulator_indices`
+/// are the positions of the accumulator limbs in the instances and the verifier also checks
+/// the accumulated pairing.
+pub fn gen_sol_verifier(
     params: &ParamsKZG<Bn256>,
-    empty_circuit: C,
+    vk: &VerifyingKey<G1Affine>,
     num_instance: Vec<usize>,
+    multi_open: MultiOpen,
+    accumulator_indices: Option<Vec<(usize, usize)>>,
 ) -> Result<String, Error> {
-    let pk = gen_pk(params, &empty_circuit);
+    match (multi_open, accumulator_indices.is_some()) {
+        (MultiOpen::Gwc, false) => {
+            gen_sol_verifier_with::<Gwc19, ()>(params, vk, num_instance, accumulator_indices)
+        }
+        (MultiOpen::Gwc, true) => {
+            gen_sol_verifier_with::<Gwc19, Limbs>(params, vk, num_instance, accumulator_indices)
+        }
+        (MultiOpen::Shplonk, false) => {
+            gen_sol_verifier_with::<Bdfg21, ()>(params, vk, num_instance, accumulator_indices)
+        }
+        (MultiOpen::Shplonk, true) => {
+            gen_sol_verifier_with::<Bdfg21, Limbs>(params, vk, num_instance, accumulator_indices)
+        }
+    }
+}
 
+fn gen_sol_verifier_with<S, AE>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    num_instance: Vec<usize>,
+    accumulator_indices: Option<Vec<(usize, usize)>>,
+) -> Result<String, Error>
+where
+    PlonkVerifier<S, AE>: SnarkVerifier<
+        G1Affine,
+        Rc<EvmLoader>,
+        VerifyingKey = KzgDecidingKey<Bn256>,
+        Protocol = PlonkProtocol<G1Affine, Rc<EvmLoader>>,
+    >,
+{
     let protocol = compile(
         params,
-        pk.get_vk(),
-        Config::kzg().with_num_instance(num_instance.clone()),
+        vk,
+        Config::kzg()
+            .with_num_instance(num_instance.clone())
+            .with_accumulator_indices(accumulator_indices),
     );
-    let vk = (params.get_g()[0], params.g2(), params.s_g2()).into();
+    let dk = (params.get_g()[0], params.g2(), params.s_g2()).into();
 
     let loader = EvmLoader::new::<Fq, Fr>();
     let protocol = protocol.loaded(&loader);
     let mut transcript = EvmTranscript::<_, Rc<EvmLoader>, _, _>::new(&loader);
 
     let instances = transcript.load_instances(num_instance);
-    let proof = match PlonkVerifier::read_proof(&vk, &protocol, &instances, &mut transcript) {
-        Ok(proof) => proof,
-        Err(_) => return Err(Error::Proof(String::from("Generate proof error"))),
+    let proof = PlonkVerifier::<S, AE>::read_proof(&dk, &protocol, &instances, &mut transcript)?;
+    PlonkVerifier::<S, AE>::verify(&dk, &protocol, &instances, &proof)?;
+
+    Ok(loader.solidity_code())
+}
+
+/// Generates a typed wrapper of the deployed verifier at the address given to its constructor,
+/// taking the `num_instance` instances separately from the proof and packing them like
+/// [`encode_calldata`].
+pub fn gen_sol_wrapper(num_instance: &[usize]) -> String {
+    format!(
+        r#"// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.19;
+
+contract VerifierWrapper {{
+    uint256 public constant NUM_INSTANCE = {};
+
+    address public immutable verifier;
+
+    constructor(address _verifier) {{
+        require(_verifier.code.length > 0, "verifier is not a contract");
+        verifier = _verifier;
+    }}
+
+    function verify(uint256[] calldata instances, bytes calldata proof) external view returns (bool) {{
+        require(instances.length == NUM_INSTANCE, "invalid number of instances");
+        (bool success, ) = verifier.staticcall(abi.encodePacked(instances, proof));
+        return success;
+    }}
+}}
+"#,
+        num_instance.iter().sum::<usize>()
+    )
+}
+
+/// Compiles solidity code with the `solc` in `PATH` and returns the deployment bytecode.
+pub fn compile_solidity(code: &str) -> Result<Vec<u8>, Error> {
+    let stdout = run_solc(&["--bin"], code)?;
+    let stdout = String::from_utf8_lossy(&stdout);
+    let binary = stdout
+        .split_ascii_whitespace()
+        .last()
+        .ok_or_else(|| Error::Solc(String::from("empty solc output")))?;
+    Ok(hex::decode(binary)?)
+}
+
+/// Settings `solc` is run with, the defaults of its command line.
+pub const SOLC_SETTINGS: &str = r#"{"optimizer":{"enabled":false,"runs":200}}"#;
+
+/// Contract compiled by `solc`.
+#[derive(Clone, Debug)]
+pub struct CompiledContract {
+    pub name: String,
+    pub abi: serde_json::Value,
+    pub bytecode: Vec<u8>,
+    pub deployed_bytecode: Vec<u8>,
+    pub solc_version: String,
+}
+
+/// Compiles solidity code with the `solc` in `PATH` and returns its contract `name`.
+pub fn compile_solidity_contract(code: &str, name: &str) -> Result<CompiledContract, Error> {
+    #[derive(Deserialize)]
+    struct Output {
+        contracts: serde_json::Map<String, serde_json::Value>,
+        version: String,
+    }
+    #[derive(Deserialize)]
+    struct Contract {
+        abi: serde_json::Value,
+        bin: String,
+        #[serde(rename = "bin-runtime")]
+        bin_runtime: String,
+    }
+
+    let stdout = run_solc(&["--combined-json", "abi,bin,bin-runtime"], code)?;
+    let output: Output = serde_json::from_slice(&stdout)?;
+    // contracts are keyed by `<source>:<name>`
+    let contract = output
+        .contracts
+        .into_iter()
+        .find_map(|(key, contract)| (key.rsplit(':').next() == Some(name)).then_some(contract))
+        .ok_or_else(|| Error::Solc(format!("no contract [{}] in output", name)))?;
+    let contract: Contract = serde_json::from_value(contract)?;
+    if contract.bin.is_empty() {
+        return Err(Error::Solc(format!(
+            "contract [{}] is not deployable",
+            name
+        )));
+    }
+    // solc before 0.8.10 writes the abi as a json string
+    let abi = match contract.abi {
+        serde_json::Value::String(abi) => serde_json::from_str(&abi)?,
+        abi => abi,
     };
+    Ok(CompiledContract {
+        name: name.to_string(),
+        abi,
+        bytecode: hex::decode(contract.bin)?,
+        deployed_bytecode: hex::decode(contract.bin_runtime)?,
+        solc_version: output.version,
+    })
+}
+
+fn run_solc(args: &[&str], code: &str) -> Result<Vec<u8>, Error> {
+    let mut cmd = Command::new("solc")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .args(args)
+        .arg("-")
+        .spawn()?;
+    cmd.stdin
+        .take()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "solc stdin is not piped"))?
+        .write_all(code.as_bytes())?;
 
-    if PlonkVerifier::verify(&vk, &protocol, &instances, &proof).is_err() {
-        return Err(Error::Verify(String::from("Verify proof error")));
+    let output = cmd.wait_with_output()?;
+    if !output.status.success() {
+        return Err(Error::Solc(String::from_utf8_lossy(&output.stderr).into()));
     }
+    Ok(output.stdout)
+}
 
-    Ok(loader.solidity_code())
+/// Result of running a verifier contract in the in-process EVM.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvmReport {
+    /// Why the deployment or the verification call failed, `None` if the proof was accepted.
+    pub revert_reason: Option<String>,
+    pub deploy_gas: u64,
+    /// Gas of the verification call, 0 if the deployment failed.
+    pub call_gas: u64,
+    pub calldata_size: usize,
+    /// Size of the deployed runtime code.
+    pub code_size: usize,
+}
+
+impl EvmReport {
+    pub fn success(&self) -> bool {
+        self.revert_reason.is_none()
+    }
+}
+
+/// Deploys `deployment_code` in the in-process EVM and calls it with the calldata of
+/// `instances` and `proof`.
+pub fn evm_verify(
+    deployment_code: Vec<u8>,
+    instances: &[Vec<Fr>],
+    proof: &[u8],
+) -> Result<EvmReport, Error> {
+    let calldata = encode_calldata(instances, proof);
+    let mut report = EvmReport {
+        revert_reason: None,
+        deploy_gas: 0,
+        call_gas: 0,
+        calldata_size: calldata.len(),
+        code_size: 0,
+    };
+
+    let mut evm = EVM::new();
+    evm.database(InMemoryDB::default());
+    evm.env.tx = TxEnv {
+        gas_limit: u64::MAX,
+        transact_to: TransactTo::Create(CreateScheme::Create),
+        data: deployment_code.into(),
+        ..Default::default()
+    };
+    let contract = match evm.transact_commit().map_err(evm_error)? {
+        ExecutionResult::Success {
+            gas_used,
+            output: Output::Create(code, Some(contract)),
+            ..
+        } => {
+            report.deploy_gas = gas_used;
+            report.code_size = code.len();
+            contract
+        }
+        result => {
+            report.deploy_gas = result.gas_used();
+            report.revert_reason = Some(format!("deployment {}", failure_reason(result)));
+            return Ok(report);
+        }
+    };
+
+    evm.env.tx = TxEnv {
+        gas_limit: u64::MAX,
+        transact_to: TransactTo::Call(contract),
+        data: calldata.into(),
+        ..Default::default()
+    };
+    let result = evm.transact_commit().map_err(evm_error)?;
+    report.call_gas = result.gas_used();
+    if !result.is_success() {
+        report.revert_reason = Some(format!("call {}", failure_reason(result)));
+    }
+    Ok(report)
+}
+
+fn evm_error<E: fmt::Debug>(e: EVMError<E>) -> Error {
+    Error::Evm(format!("{:?}", e))
+}
+
+/// Describes a failed execution, decoding `Error(string)` revert data.
+fn failure_reason(result: ExecutionResult) -> String {
+    match result {
+        ExecutionResult::Revert { output, .. } => {
+            // selector of `Error(string)`, then the offset and length of the message
+            match output.get(..4) {
+                Some([0x08, 0xc3, 0x79, 0xa0]) if output.len() >= 68 => {
+                    let len = output[36..68].iter().fold(0usize, |len, byte| {
+                        len.saturating_mul(256).saturating_add(*byte as usize)
+                    });
+                    let message = &output[68..68 + len.min(output.len() - 68)];
+                    format!("reverted: {}", String::from_utf8_lossy(message))
+                }
+                _ if output.is_empty() => String::from("reverted"),
+                _ => format!("reverted: 0x{}", hex::encode(&output)),
+            }
+        }
+        ExecutionResult::Halt { reason, .. } => format!("halted: {:?}", reason),
+        ExecutionResult::Success { .. } => String::from("returned no contract"),
+    }
+}
+
+/// Runs `circuit` against `instances` on the mock prover and returns every failing gate,
+/// lookup and copy constraint, which name the regions and columns involved. This is much
+/// cheaper than keygen and proving, so it works as a pre-flight check of the witness.
+pub fn mock_prove<C: Circuit<Fr>>(
+    k: u32,
+    circuit: &C,
+    instances: Vec<Vec<Fr>>,
+) -> Result<Vec<VerifyFailure>, Error> {
+    let prover = MockProver::run(k, circuit, instances)?;
+    Ok(prover.verify().err().unwrap_or_default())
 }
 
 pub fn gen_proof<C: Circuit<Fr>>(
@@ -76,43 +584,308 @@ pub fn gen_proof<C: Circuit<Fr>>(
     pk: &ProvingKey<G1Affine>,
     circuit: C,
     instances: &[Vec<Fr>],
-) -> Vec<u8> {
+    multi_open: MultiOpen,
+    transcript: Transcript,
+) -> Result<Vec<u8>, Error> {
+    match transcript {
+        Transcript::Evm => gen_proof_with::<_, _, EvmTranscript<_, _, _, _>>(
+            params, pk, circuit, instances, multi_open,
+        ),
+        Transcript::Poseidon => gen_proof_with::<_, _, PoseidonTranscript<NativeLoader, _>>(
+            params, pk, circuit, instances, multi_open,
+        ),
+        Transcript::Blake2b => gen_proof_with::<_, _, Blake2bWrite<_, _, Challenge255<_>>>(
+            params, pk, circuit, instances, multi_open,
+        ),
+    }
+}
+
+fn gen_proof_with<C, E, T>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: C,
+    instances: &[Vec<Fr>],
+    multi_open: MultiOpen,
+) -> Result<Vec<u8>, Error>
+where
+    C: Circuit<Fr>,
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptWriterBuffer<Vec<u8>, G1Affine, E>,
+{
     let instances = instances
         .iter()
         .map(|instances| instances.as_slice())
         .collect_vec();
-    let mut transcript = TranscriptWriterBuffer::<_, G1Affine, _>::init(Vec::new());
-    create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, EvmTranscript<_, _, _, _>, _>(
-        params,
-        pk,
-        &[circuit],
-        &[instances.as_slice()],
-        OsRng,
-        &mut transcript,
-    )
-    .unwrap();
-    transcript.finalize()
+    let mut transcript = T::init(Vec::new());
+    match multi_open {
+        MultiOpen::Gwc => create_proof::<KZGCommitmentScheme<Bn256>, ProverGWC<_>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[instances.as_slice()],
+            OsRng,
+            &mut transcript,
+        )?,
+        MultiOpen::Shplonk => {
+            create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+                params,
+                pk,
+                &[circuit],
+                &[instances.as_slice()],
+                OsRng,
+                &mut transcript,
+            )?
+        }
+    }
+    Ok(transcript.finalize())
 }
 
 pub fn verify_proof(
     params: &ParamsKZG<Bn256>,
-    pk: &ProvingKey<G1Affine>,
+    vk: &VerifyingKey<G1Affine>,
     proof: Vec<u8>,
     instances: &[Vec<Fr>],
-) -> bool {
+    multi_open: MultiOpen,
+    transcript: Transcript,
+) -> Result<bool, Error> {
+    match transcript {
+        Transcript::Evm => verify_proof_with::<_, EvmTranscript<_, _, _, _>>(
+            params, vk, &proof, instances, multi_open,
+        ),
+        Transcript::Poseidon => verify_proof_with::<_, PoseidonTranscript<NativeLoader, _>>(
+            params, vk, &proof, instances, multi_open,
+        ),
+        Transcript::Blake2b => verify_proof_with::<_, Blake2bRead<_, _, Challenge255<_>>>(
+            params, vk, &proof, instances, multi_open,
+        ),
+    }
+}
+
+fn verify_proof_with<'a, E, T>(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &'a [u8],
+    instances: &[Vec<Fr>],
+    multi_open: MultiOpen,
+) -> Result<bool, Error>
+where
+    E: EncodedChallenge<G1Affine>,
+    T: TranscriptReadBuffer<&'a [u8], G1Affine, E>,
+{
     let instances = instances
         .iter()
         .map(|instances| instances.as_slice())
         .collect_vec();
-    let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof.as_slice());
-    VerificationStrategy::<_, VerifierGWC<_>>::finalize(
-        plonk::verify_proof::<_, VerifierGWC<_>, _, EvmTranscript<_, _, _, _>, _>(
-            params.verifier_params(),
-            pk.get_vk(),
-            AccumulatorStrategy::new(params.verifier_params()),
-            &[instances.as_slice()],
-            &mut transcript,
+    let mut transcript = T::init(proof);
+    let accept = match multi_open {
+        MultiOpen::Gwc => {
+            VerificationStrategy::<_, VerifierGWC<_>>::finalize(plonk::verify_proof::<
+                _,
+                VerifierGWC<_>,
+                _,
+                T,
+                _,
+            >(
+                params.verifier_params(),
+                vk,
+                AccumulatorStrategy::new(params.verifier_params()),
+                &[instances.as_slice()],
+                &mut transcript,
+            )?)
+        }
+        MultiOpen::Shplonk => {
+            VerificationStrategy::<_, VerifierSHPLONK<_>>::finalize(plonk::verify_proof::<
+                _,
+                VerifierSHPLONK<_>,
+                _,
+                T,
+                _,
+            >(
+                params.verifier_params(),
+                vk,
+                AccumulatorStrategy::new(params.verifier_params()),
+                &[instances.as_slice()],
+                &mut transcript,
+            )?)
+        }
+    };
+    Ok(accept)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use halo2_curves::bn256::Fr;
+    use halo2_proofs::{circuit::Value, poly::commitment::Params, SerdeFormat};
+    use revm::primitives::{Bytes, ExecutionResult};
+
+    use super::{
+        downsize_srs, evm_verify, failure_reason, gen_pk, gen_proof, gen_sol_wrapper, gen_srs,
+        mock_prove, read_pk, read_vk, verify_proof, write_pk, write_vk, Error, MultiOpen,
+        Transcript,
+    };
+    use crate::circuits::simple::SimpleCircuit;
+
+    #[test]
+    fn keys_round_trip() {
+        let params = gen_srs(4).unwrap();
+        let constant = Fr::from(7);
+        let empty_circuit = SimpleCircuit {
+            constant,
+            a: Value::unknown(),
+            b: Value::unknown(),
+        };
+        let pk = gen_pk(&params, &empty_circuit).unwrap();
+
+        let dir = env::temp_dir();
+        let vk_file = dir.join("halo2-evm-verifier-test-vk.bin");
+        let pk_file = dir.join("halo2-evm-verifier-test-pk.bin");
+        write_vk(pk.get_vk(), &vk_file, SerdeFormat::RawBytes).unwrap();
+        write_pk(&pk, &pk_file, SerdeFormat::RawBytes).unwrap();
+        let vk = read_vk::<SimpleCircuit<Fr>>(&vk_file, SerdeFormat::RawBytes).unwrap();
+        let pk = read_pk::<SimpleCircuit<Fr>>(&pk_file, SerdeFormat::RawBytes).unwrap();
+
+        let a = Fr::from(3);
+        let b = Fr::from(5);
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let instances = vec![vec![constant * a.square() * b.square()]];
+        for multi_open in [MultiOpen::Gwc, MultiOpen::Shplonk] {
+            for transcript in [Transcript::Evm, Transcript::Poseidon, Transcript::Blake2b] {
+                let proof = gen_proof(
+                    &params,
+                    &pk,
+                    circuit.clone(),
+                    &instances,
+                    multi_open,
+                    transcript,
+                )
+                .unwrap();
+                assert!(
+                    verify_proof(&params, &vk, proof, &instances, multi_open, transcript).unwrap()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_witness() {
+        let params = gen_srs(4).unwrap();
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::unknown(),
+            b: Value::unknown(),
+        };
+        let pk = gen_pk(&params, &circuit).unwrap();
+
+        // proving without witnesses must report an error instead of panicking
+        let instances = [vec![Fr::from(1)]];
+        assert!(gen_proof(
+            &params,
+            &pk,
+            circuit,
+            &instances,
+            MultiOpen::Gwc,
+            Transcript::Evm
         )
-        .unwrap(),
-    )
+        .is_err());
+        assert!(matches!(gen_srs(0), Err(Error::InvalidK(0))));
+
+        // the mock prover reports the wrong instance instead
+        let circuit = SimpleCircuit {
+            constant: Fr::from(7),
+            a: Value::known(Fr::from(3)),
+            b: Value::known(Fr::from(5)),
+        };
+        assert!(!mock_prove(4, &circuit, vec![vec![Fr::from(1)]])
+            .unwrap()
+            .is_empty());
+        assert!(mock_prove(4, &circuit, vec![vec![Fr::from(1575)]])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn evm_report() {
+        // init code returning the runtime code appended after its 12 bytes
+        let deploy = |runtime: &[u8]| {
+            let mut code = vec![
+                0x60,
+                runtime.len() as u8,
+                0x60,
+                0x0c,
+                0x60,
+                0x00,
+                0x39,
+                0x60,
+                runtime.len() as u8,
+                0x60,
+                0x00,
+                0xf3,
+            ];
+            code.extend_from_slice(runtime);
+            code
+        };
+        let instances = [vec![Fr::from(1575)]];
+        let proof = [0u8; 64];
+
+        // STOP
+        let report = evm_verify(deploy(&[0x00]), &instances, &proof).unwrap();
+        assert!(report.success());
+        assert!(report.deploy_gas > 0 && report.call_gas > 0);
+        assert_eq!(report.code_size, 1);
+        assert_eq!(report.calldata_size, 32 + 64);
+
+        // PUSH1 0, PUSH1 0, REVERT
+        let report =
+            evm_verify(deploy(&[0x60, 0x00, 0x60, 0x00, 0xfd]), &instances, &proof).unwrap();
+        assert!(!report.success());
+        assert_eq!(report.revert_reason.as_deref(), Some("call reverted"));
+
+        // deployment reverts before returning any code
+        let report = evm_verify(vec![0x60, 0x00, 0x60, 0x00, 0xfd], &instances, &proof).unwrap();
+        assert_eq!(report.revert_reason.as_deref(), Some("deployment reverted"));
+        assert_eq!(report.call_gas, 0);
+    }
+
+    #[test]
+    fn sol_wrapper() {
+        let code = gen_sol_wrapper(&[3, 2]);
+        assert!(code.contains("uint256 public constant NUM_INSTANCE = 5;"));
+        assert!(code.contains("abi.encodePacked(instances, proof)"));
+    }
+
+    #[test]
+    fn downsize() {
+        let params = gen_srs(5).unwrap();
+        let small = downsize_srs(&params, 4).unwrap();
+        assert_eq!(small.k(), 4);
+        assert_eq!(small.get_g()[..16], params.get_g()[..16]);
+        assert!(matches!(downsize_srs(&small, 5), Err(Error::InvalidK(5))));
+    }
+
+    #[test]
+    fn revert_reason() {
+        let revert = |len: [u8; 32], message: &[u8]| {
+            let mut output = vec![0x08, 0xc3, 0x79, 0xa0];
+            output.extend_from_slice(&[0; 32]);
+            output.extend_from_slice(&len);
+            output.extend_from_slice(message);
+            failure_reason(ExecutionResult::Revert {
+                gas_used: 0,
+                output: Bytes::from(output),
+            })
+        };
+
+        let mut len = [0; 32];
+        len[31] = 5;
+        assert_eq!(revert(len, b"error"), "reverted: error");
+        // lengths past the output, up to overflowing usize, only keep what was returned
+        assert_eq!(revert(len, b"err"), "reverted: err");
+        assert_eq!(revert([0xff; 32], b"error"), "reverted: error");
+    }
 }
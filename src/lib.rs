@@ -1,3 +1,7 @@
+pub mod aggregation;
 pub mod circuits;
+pub mod contract;
 pub mod generator;
 pub mod opts;
+pub mod proof;
+pub mod srs;
@@ -1,31 +1,32 @@
-use clap::Parser;
-use halo2_curves::bn256::{Bn256, Fr, G1Affine};
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use halo2_curves::bn256::{Bn256, Fr};
 use halo2_proofs::{
-    circuit::Value,
     dev::CircuitLayout,
-    plonk,
-    poly::{
-        commitment::{Params, ParamsProver},
-        kzg::{commitment::ParamsKZG, multiopen::VerifierGWC, strategy::AccumulatorStrategy},
-        VerificationStrategy,
-    },
-    transcript::TranscriptReadBuffer,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    SerdeFormat,
 };
-use hex::FromHex;
 use itertools::Itertools;
-use snark_verifier::{
-    loader::evm::{self, deploy_and_call, encode_calldata},
-    system::halo2::transcript::evm::EvmTranscript,
-};
+use rand::rngs::OsRng;
 use std::{
     fs::{self, File},
-    io::BufReader,
+    path::Path,
+    process,
 };
 
 use halo2_evm_verifier::{
-    circuits::simple::SimpleCircuit,
-    generator::{gen_pk, gen_proof, gen_sol_verifier, gen_srs, verify_proof},
-    opts::{Opts, Subcommands},
+    circuits::{
+        circuit_names, gadgets::Field, inputs::read_inputs, stats::CircuitStats, visit_circuit,
+        CircuitArgs, CircuitVisitor, RegisteredCircuit,
+    },
+    contract::{contract_name, write_foundry_project, VerifierArtifact},
+    generator::{
+        self, compile_solidity, compile_solidity_contract, downsize_srs, evm_verify, gen_pk,
+        gen_proof, gen_sol_verifier, gen_sol_wrapper, gen_srs, mock_prove, read_pk, read_vk,
+        write_params, write_pk, write_vk, MultiOpen, Transcript,
+    },
+    opts::{Opts, ParamsAction, Subcommands},
+    proof::{decode_calldata, read_instances, read_proof, ProofArtifact},
+    srs::{contribute, import_srs, verify_ceremony, Contribution},
 };
 use plotters::prelude::{BitMapBackend, IntoDrawingArea, WHITE};
 
@@ -33,156 +34,761 @@ fn main() {
     let opts = Opts::parse();
 
     match opts.sub {
-        Subcommands::Params { file } => {
-            let mut params_file = File::create(file.clone())
-                .unwrap_or_else(|_| panic!("create params file [{}] error", file));
-            let params = gen_srs(4);
-            params.write(&mut params_file).expect("write file error");
+        Subcommands::Params {
+            action: None,
+            file,
+            k,
+            circuit,
+            args,
+        } => {
+            let k = k.unwrap_or_else(|| {
+                let mut circuit_args = CircuitArgs::new();
+                circuit_args.extend(args);
+                visit_circuit(&circuit, StatsCommand { args: circuit_args })
+                    .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit))
+                    .min_k
+            });
+            let params = gen_srs(k).unwrap_or_else(|e| panic!("generate params error: {}", e));
+            write_params(&params, &file)
+                .unwrap_or_else(|e| panic!("write params file [{}] error: {}", file, e));
+        }
+
+        Subcommands::Params {
+            action: Some(ParamsAction::Downsize { input, file, k }),
+            ..
+        } => {
+            let params = read_params(input);
+            let params =
+                downsize_srs(&params, k).unwrap_or_else(|e| panic!("downsize params error: {}", e));
+            write_params(&params, &file)
+                .unwrap_or_else(|e| panic!("write params file [{}] error: {}", file, e));
+        }
+
+        Subcommands::Params {
+            action:
+                Some(ParamsAction::Contribute {
+                    input,
+                    file,
+                    contribution,
+                }),
+            ..
+        } => {
+            let params = read_params(input);
+            let (params, proof) = contribute(&params, OsRng)
+                .unwrap_or_else(|e| panic!("contribute params error: {}", e));
+            write_params(&params, &file)
+                .unwrap_or_else(|e| panic!("write params file [{}] error: {}", file, e));
+            let mut contribution_file = File::create(&contribution)
+                .unwrap_or_else(|_| panic!("create contribution file [{}] error", contribution));
+            proof
+                .write(&mut contribution_file)
+                .unwrap_or_else(|e| panic!("write contribution error: {}", e));
+            println!("Contributed params hash: 0x{}", hex::encode(proof.next));
+        }
+
+        Subcommands::Params {
+            action:
+                Some(ParamsAction::VerifyCeremony {
+                    params,
+                    contributions,
+                }),
+            ..
+        } => {
+            let params = params.into_iter().map(read_params).collect_vec();
+            let contributions = contributions
+                .iter()
+                .map(|file| {
+                    let raw = fs::read(file)
+                        .unwrap_or_else(|_| panic!("read contribution file [{}] error", file));
+                    Contribution::read(&mut raw.as_slice())
+                        .unwrap_or_else(|e| panic!("read contribution [{}] error: {}", file, e))
+                })
+                .collect_vec();
+
+            match verify_ceremony(&params, &contributions) {
+                Ok(()) => println!("Verify ceremony result: true"),
+                Err(e) => {
+                    println!("Verify ceremony result: false, {}", e);
+                    process::exit(1);
+                }
+            }
+        }
+
+        Subcommands::Params {
+            action: Some(ParamsAction::Import { input, file, k }),
+            ..
+        } => {
+            let params = import_srs(&input, k)
+                .unwrap_or_else(|e| panic!("import params file [{}] error: {}", input, e));
+            write_params(&params, &file)
+                .unwrap_or_else(|e| panic!("write params file [{}] error: {}", file, e));
+        }
+
+        Subcommands::Keygen {
+            circuit,
+            params,
+            vk,
+            pk,
+            format,
+            constant,
+            args,
+        } => {
+            let mut circuit_args = CircuitArgs::new().with("constant", constant);
+            circuit_args.extend(args);
+
+            visit_circuit(
+                &circuit,
+                KeygenCommand {
+                    params,
+                    vk,
+                    pk,
+                    format: format.into(),
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
         }
 
         Subcommands::Solidity {
+            circuit,
             file,
             params,
-            constant,
+            vk,
+            format,
+            multi_open,
             bytecode,
+            wrapper,
+            artifact,
+            args,
         } => {
-            // TODO support select circuit
-            let constant = Fr::from(constant);
-            let empty_circuit = SimpleCircuit {
-                constant,
-                a: Value::unknown(),
-                b: Value::unknown(),
-            };
-            let params_raw = fs::read(params).expect("read params file error");
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params_raw.as_slice()))
-                .expect("restore params error");
+            let mut circuit_args = CircuitArgs::new();
+            circuit_args.extend(args);
 
-            let sol_code = gen_sol_verifier(&params, empty_circuit, vec![1])
-                .expect("generate solidity file error");
-            println!(
-                "Generated verifier contract size: {}",
-                evm::compile_solidity(sol_code.as_str()).len()
-            );
-            if bytecode {
-                fs::write(
+            visit_circuit(
+                &circuit,
+                SolidityCommand {
                     file,
-                    format!(
-                        "0x{}",
-                        hex::encode(evm::compile_solidity(sol_code.as_str()))
-                    ),
-                )
-                .expect("write verifier bytecode error");
-            } else {
-                fs::write(file, sol_code).expect("write verifier solidity error");
-            }
+                    params,
+                    vk,
+                    format: format.into(),
+                    multi_open,
+                    bytecode,
+                    wrapper,
+                    artifact,
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
+        }
+
+        Subcommands::Scaffold {
+            dir,
+            proof,
+            params,
+            vk,
+            format,
+            args,
+        } => {
+            let artifact = ProofArtifact::read(&proof)
+                .unwrap_or_else(|e| panic!("read proof file [{}] error: {}", proof, e));
+            assert!(
+                artifact.transcript == Transcript::Evm,
+                "on-chain verification needs the evm transcript"
+            );
+            let mut circuit_args = CircuitArgs::new();
+            circuit_args.extend(args);
+
+            let circuit = artifact.circuit.clone();
+            visit_circuit(
+                &circuit,
+                ScaffoldCommand {
+                    dir,
+                    params,
+                    vk,
+                    format: format.into(),
+                    artifact,
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
         }
 
-        Subcommands::Graph { file, title, k } => {
-            let circuit = SimpleCircuit::<Fr>::default();
-            let root = BitMapBackend::new(&file, (1024, 768)).into_drawing_area();
-            root.fill(&WHITE).unwrap();
-            let root = root.titled(&title, ("sans-serif", 60)).unwrap();
-            CircuitLayout::default()
-                .show_labels(true)
-                .render(k, &circuit, &root)
-                .unwrap();
+        Subcommands::Graph {
+            circuit,
+            file,
+            title,
+            k,
+            args,
+        } => {
+            let mut circuit_args = CircuitArgs::new();
+            circuit_args.extend(args);
+
+            visit_circuit(
+                &circuit,
+                GraphCommand {
+                    file,
+                    title,
+                    k,
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
         }
 
         Subcommands::Proof {
+            circuit,
             file,
             verify,
             params,
+            pk,
+            format,
+            multi_open,
+            transcript,
             constant,
-            a,
-            b,
+            inputs,
+            args,
         } => {
-            // TODO support select circuit
-            let constant = Fr::from(constant);
-            let a = Fr::from(a);
-            let b = Fr::from(b);
-            let params_raw = fs::read(params).expect("read params file error");
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params_raw.as_slice()))
-                .expect("restore params error");
-
-            let empty_circuit = SimpleCircuit {
-                constant,
-                a: Value::unknown(),
-                b: Value::unknown(),
-            };
-            let pk = gen_pk(&params, &empty_circuit);
+            let mut circuit_args = CircuitArgs::new().with("constant", constant);
+            circuit_args.extend(args);
 
-            let c = constant * a.square() * b.square();
-            let circuit = SimpleCircuit {
-                constant,
-                a: Value::known(a),
-                b: Value::known(b),
+            visit_circuit(
+                &circuit,
+                ProofCommand {
+                    file,
+                    verify,
+                    params,
+                    pk,
+                    format: format.into(),
+                    multi_open,
+                    transcript,
+                    args: circuit_args,
+                    inputs,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
+        }
+
+        Subcommands::Stats { circuit, args } => {
+            let mut circuit_args = CircuitArgs::new();
+            circuit_args.extend(args);
+
+            let names = match circuit {
+                Some(circuit) => vec![circuit],
+                None => circuit_names().into_iter().map(String::from).collect(),
             };
-            let instances = vec![vec![c]];
-            let proof = gen_proof(&params, &pk, circuit.clone(), &instances);
-
-            let accept = {
-                let instances = instances
-                    .iter()
-                    .map(|instances| instances.as_slice())
-                    .collect_vec();
-                let mut transcript = TranscriptReadBuffer::<_, G1Affine, _>::init(proof.as_slice());
-                VerificationStrategy::<_, VerifierGWC<_>>::finalize(
-                    plonk::verify_proof::<_, VerifierGWC<_>, _, EvmTranscript<_, _, _, _>, _>(
-                        params.verifier_params(),
-                        pk.get_vk(),
-                        AccumulatorStrategy::new(params.verifier_params()),
-                        &[instances.as_slice()],
-                        &mut transcript,
-                    )
-                    .unwrap(),
+            for (idx, name) in names.iter().enumerate() {
+                let stats = visit_circuit(
+                    name,
+                    StatsCommand {
+                        args: circuit_args.clone(),
+                    },
                 )
-            };
-            assert!(accept);
-
-            let calldata = encode_calldata(&instances, &proof);
-            if verify {
-                let deployment_code = gen_sol_verifier(&params, empty_circuit, vec![1])
-                    .expect("generate contract error");
-                let deployment_code = evm::compile_solidity(&deployment_code);
-                let gas_cost = deploy_and_call(deployment_code.clone(), calldata.clone())
-                    .expect("verify proof error");
-                println!("verified gas cost: {}", gas_cost);
+                .unwrap_or_else(|| panic!("unknown circuit [{}]", name));
+                if idx > 0 {
+                    println!();
+                }
+                println!("circuit [{}]\n{}", name, stats);
             }
+        }
 
-            let output = format!(
-                r#"{{
-    "proof": "0x{}",
-    "calldata": "0x{}"
-}}"#,
-                hex::encode(&proof),
-                hex::encode(&calldata),
-            );
+        Subcommands::Check {
+            circuit,
+            k,
+            constant,
+            inputs,
+            instances,
+            args,
+        } => {
+            let mut circuit_args = CircuitArgs::new().with("constant", constant);
+            circuit_args.extend(args);
+            let instances = instances.map(|instances| {
+                read_instances(&instances)
+                    .unwrap_or_else(|e| panic!("read instances file [{}] error: {}", instances, e))
+            });
 
-            fs::write(file, output).expect("write proof file error");
+            let satisfied = visit_circuit(
+                &circuit,
+                CheckCommand {
+                    k,
+                    inputs,
+                    instances,
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
+            if !satisfied {
+                process::exit(1);
+            }
         }
 
         Subcommands::Verify {
             params,
-            constant,
-            c,
+            vk,
+            format,
             proof,
+            instances,
+            calldata,
+            circuit,
+            multi_open,
+            transcript,
+            constant,
+            args,
         } => {
-            let constant = Fr::from(constant);
-            let params_raw = fs::read(params).expect("read params file error");
-            let params = ParamsKZG::<Bn256>::read(&mut BufReader::new(params_raw.as_slice()))
-                .expect("restore params error");
-
-            let empty_circuit = SimpleCircuit {
-                constant,
-                a: Value::unknown(),
-                b: Value::unknown(),
+            let mut circuit_args = CircuitArgs::new().with("constant", constant);
+            circuit_args.extend(args);
+
+            let (circuit, input) = match (instances, calldata) {
+                (_, Some(calldata)) => {
+                    let calldata = match calldata.strip_prefix("0x") {
+                        Some(calldata) => hex::decode(calldata)
+                            .unwrap_or_else(|e| panic!("decode calldata error: {}", e)),
+                        None => read_proof(&calldata).unwrap_or_else(|e| {
+                            panic!("read calldata file [{}] error: {}", calldata, e)
+                        }),
+                    };
+                    (
+                        circuit,
+                        ProofInput::Calldata {
+                            multi_open,
+                            calldata,
+                        },
+                    )
+                }
+                (Some(instances), None) => {
+                    let instances = read_instances(&instances).unwrap_or_else(|e| {
+                        panic!("read instances file [{}] error: {}", instances, e)
+                    });
+                    let proof = read_proof(&proof)
+                        .unwrap_or_else(|e| panic!("read proof file [{}] error: {}", proof, e));
+                    let input = ProofInput::Raw {
+                        multi_open,
+                        transcript,
+                        instances,
+                        proof,
+                    };
+                    (circuit, input)
+                }
+                (None, None) => {
+                    let artifact = ProofArtifact::read(&proof)
+                        .unwrap_or_else(|e| panic!("read proof file [{}] error: {}", proof, e));
+                    (artifact.circuit.clone(), ProofInput::Artifact(artifact))
+                }
+            };
+            visit_circuit(
+                &circuit,
+                VerifyCommand {
+                    params,
+                    vk,
+                    format: format.into(),
+                    input,
+                    args: circuit_args,
+                },
+            )
+            .unwrap_or_else(|| panic!("unknown circuit [{}]", circuit));
+        }
+    }
+}
+
+/// Exits with a usage error, as clap does for invalid arguments.
+fn usage_error<T>(message: String) -> T {
+    Opts::command()
+        .error(ErrorKind::ValueValidation, message)
+        .exit()
+}
+
+fn read_params(file: String) -> ParamsKZG<Bn256> {
+    generator::read_params(&file)
+        .unwrap_or_else(|e| panic!("read params file [{}] error: {}", file, e))
+}
+
+/// Warns when `params` can't hold `circuit`, keygen and proving would fail with them.
+fn check_params<C: RegisteredCircuit>(params: &ParamsKZG<Bn256>, circuit: &C) {
+    let k = circuit.required_k();
+    if params.k() < k {
+        eprintln!(
+            "warning: params k = {} is too small, circuit [{}] requires k >= {}",
+            params.k(),
+            C::NAME,
+            k
+        );
+    }
+}
+
+/// Downsizes `params` to `k` when they are larger, so keys and proofs use the smallest domain
+/// which holds the circuit, and verifiers use the domain of the vk.
+fn fit_params(params: ParamsKZG<Bn256>, k: u32) -> ParamsKZG<Bn256> {
+    if params.k() > k {
+        downsize_srs(&params, k).unwrap_or_else(|e| panic!("downsize params error: {}", e))
+    } else {
+        params
+    }
+}
+
+struct KeygenCommand {
+    params: String,
+    vk: String,
+    pk: String,
+    format: SerdeFormat,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for KeygenCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let params = read_params(self.params);
+
+        let empty_circuit = C::empty(&self.args).unwrap_or_else(usage_error);
+        check_params(&params, &empty_circuit);
+        let params = fit_params(params, empty_circuit.required_k());
+        let pk = gen_pk(&params, &empty_circuit)
+            .unwrap_or_else(|e| panic!("generate keys error: {}", e));
+        write_vk(pk.get_vk(), &self.vk, self.format)
+            .unwrap_or_else(|e| panic!("write vk file [{}] error: {}", self.vk, e));
+        write_pk(&pk, &self.pk, self.format)
+            .unwrap_or_else(|e| panic!("write pk file [{}] error: {}", self.pk, e));
+    }
+}
+
+struct SolidityCommand {
+    file: String,
+    params: String,
+    vk: String,
+    format: SerdeFormat,
+    multi_open: MultiOpen,
+    bytecode: bool,
+    wrapper: Option<String>,
+    artifact: Option<String>,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for SolidityCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let empty_circuit = C::empty(&self.args).unwrap_or_else(usage_error);
+        let num_instance = empty_circuit.num_instance();
+        let params = read_params(self.params);
+        check_params(&params, &empty_circuit);
+        let vk = read_vk::<C>(&self.vk, self.format)
+            .unwrap_or_else(|e| panic!("read vk file [{}] error: {}", self.vk, e));
+        let params = fit_params(params, vk.get_domain().k());
+
+        if let Some(file) = &self.wrapper {
+            fs::write(file, gen_sol_wrapper(&num_instance)).expect("write wrapper solidity error");
+        }
+
+        let sol_code = gen_sol_verifier(&params, &vk, num_instance.clone(), self.multi_open, None)
+            .unwrap_or_else(|e| panic!("generate solidity file error: {}", e));
+        let name = contract_name(&sol_code).expect("no contract in verifier solidity");
+        let contract = compile_solidity_contract(&sol_code, name)
+            .unwrap_or_else(|e| panic!("compile solidity error: {}", e));
+        println!(
+            "Generated verifier contract size: {}",
+            contract.bytecode.len()
+        );
+        if self.bytecode {
+            fs::write(&self.file, format!("0x{}", hex::encode(&contract.bytecode)))
+                .expect("write verifier bytecode error");
+        } else {
+            fs::write(&self.file, sol_code).expect("write verifier solidity error");
+        }
+
+        if let Some(file) = &self.artifact {
+            let source_name = match Path::new(&self.file).file_name() {
+                Some(name) if !self.bytecode => name.to_string_lossy().into_owned(),
+                _ => String::from("Verifier.sol"),
             };
-            let pk = gen_pk(&params, &empty_circuit);
-            let instances = vec![vec![Fr::from(c)]];
-            let proof = Vec::from_hex(proof).unwrap();
-            let result = verify_proof(&params, &pk, proof, &instances);
+            VerifierArtifact::new(
+                contract,
+                &source_name,
+                C::NAME,
+                &vk,
+                self.multi_open,
+                num_instance,
+            )
+            .and_then(|artifact| artifact.write(file))
+            .unwrap_or_else(|e| panic!("write artifact file [{}] error: {}", file, e));
+        }
+    }
+}
+
+struct ScaffoldCommand {
+    dir: String,
+    params: String,
+    vk: String,
+    format: SerdeFormat,
+    artifact: ProofArtifact,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for ScaffoldCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let empty_circuit = C::empty(&self.args).unwrap_or_else(usage_error);
+        // the forge test and wrapper are written from the proof's instances
+        let num_instance = self
+            .artifact
+            .instances
+            .iter()
+            .map(Vec::len)
+            .collect::<Vec<_>>();
+        if num_instance != empty_circuit.num_instance() {
+            usage_error::<()>(format!(
+                "proof has {:?} instances but circuit [{}] expects {:?}, check its arguments",
+                num_instance,
+                C::NAME,
+                empty_circuit.num_instance()
+            ));
+        }
+        let params = read_params(self.params);
+        check_params(&params, &empty_circuit);
+        let vk = read_vk::<C>(&self.vk, self.format)
+            .unwrap_or_else(|e| panic!("read vk file [{}] error: {}", self.vk, e));
+        assert!(
+            vk.transcript_repr() == self.artifact.vk_hash,
+            "proof was not created with vk file [{}]",
+            self.vk
+        );
+        let params = fit_params(params, vk.get_domain().k());
+
+        let sol_code = gen_sol_verifier(&params, &vk, num_instance, self.artifact.multi_open, None)
+            .unwrap_or_else(|e| panic!("generate solidity file error: {}", e));
+        write_foundry_project(&self.dir, &sol_code, &self.artifact)
+            .unwrap_or_else(|e| panic!("write foundry project [{}] error: {}", self.dir, e));
+        println!(
+            "Generated foundry project, run `forge test` in {}",
+            self.dir
+        );
+    }
+}
+
+struct GraphCommand {
+    file: String,
+    title: Option<String>,
+    k: Option<u32>,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for GraphCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let circuit = C::empty(&self.args).unwrap_or_else(usage_error);
+        let title = self.title.unwrap_or_else(|| format!("{} circuit", C::NAME));
+        let root = BitMapBackend::new(&self.file, (1024, 768)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let root = root.titled(&title, ("sans-serif", 60)).unwrap();
+        CircuitLayout::default()
+            .show_labels(true)
+            .render(
+                self.k.unwrap_or_else(|| circuit.required_k()),
+                &circuit,
+                &root,
+            )
+            .unwrap();
+    }
+}
+
+struct StatsCommand {
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for StatsCommand {
+    type Output = CircuitStats;
+
+    fn visit<C: RegisteredCircuit>(self) -> CircuitStats {
+        CircuitStats::measure(&C::empty(&self.args).unwrap_or_else(usage_error))
+            .unwrap_or_else(|e| panic!("measure circuit [{}] error: {}", C::NAME, e))
+    }
+}
+
+struct CheckCommand {
+    k: Option<u32>,
+    inputs: Option<String>,
+    instances: Option<Vec<Vec<Fr>>>,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for CheckCommand {
+    type Output = bool;
+
+    fn visit<C: RegisteredCircuit>(self) -> bool {
+        let inputs = match &self.inputs {
+            Some(file) => read_inputs::<C::Inputs>(file)
+                .unwrap_or_else(|e| panic!("read inputs file [{}] error: {}", file, e)),
+            None => C::Inputs::default(),
+        };
+        let (circuit, expected) = C::witnessed(&self.args, inputs).unwrap_or_else(usage_error);
+        let k = self.k.unwrap_or_else(|| circuit.required_k());
+        let instances = self.instances.unwrap_or(expected);
+
+        let failures = mock_prove(k, &circuit, instances)
+            .unwrap_or_else(|e| panic!("mock prove error: {}", e));
+        for failure in &failures {
+            println!("{}", failure);
+        }
+        if failures.is_empty() {
+            println!("circuit [{}] is satisfied", C::NAME);
+        } else {
+            println!(
+                "circuit [{}] has {} failed constraint(s)",
+                C::NAME,
+                failures.len()
+            );
+        }
+        failures.is_empty()
+    }
+}
+
+struct ProofCommand {
+    file: String,
+    verify: bool,
+    params: String,
+    pk: String,
+    format: SerdeFormat,
+    multi_open: MultiOpen,
+    transcript: Transcript,
+    args: CircuitArgs,
+    inputs: Option<String>,
+}
+
+impl CircuitVisitor for ProofCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let params = read_params(self.params);
+        let pk = read_pk::<C>(&self.pk, self.format)
+            .unwrap_or_else(|e| panic!("read pk file [{}] error: {}", self.pk, e));
+
+        let inputs = match &self.inputs {
+            Some(file) => read_inputs::<C::Inputs>(file)
+                .unwrap_or_else(|e| panic!("read inputs file [{}] error: {}", file, e)),
+            None => C::Inputs::default(),
+        };
+        let (circuit, instances) = C::witnessed(&self.args, inputs).unwrap_or_else(usage_error);
+        check_params(&params, &circuit);
+        let params = fit_params(params, pk.get_vk().get_domain().k());
+        let num_instance = circuit.num_instance();
+        let proof = gen_proof(
+            &params,
+            &pk,
+            circuit,
+            &instances,
+            self.multi_open,
+            self.transcript,
+        )
+        .unwrap_or_else(|e| panic!("generate proof error: {}", e));
+
+        let artifact = ProofArtifact::new(
+            C::NAME,
+            pk.get_vk(),
+            self.multi_open,
+            self.transcript,
+            instances,
+            proof,
+        );
+        let accept = artifact
+            .verify(&params, pk.get_vk())
+            .unwrap_or_else(|e| panic!("verify proof error: {}", e));
+        assert!(accept);
 
-            println!("Verify proof result: {}", result);
+        if self.verify {
+            assert!(
+                self.transcript == Transcript::Evm,
+                "on-chain verification needs the evm transcript"
+            );
+            let deployment_code =
+                gen_sol_verifier(&params, pk.get_vk(), num_instance, self.multi_open, None)
+                    .unwrap_or_else(|e| panic!("generate contract error: {}", e));
+            let deployment_code = compile_solidity(&deployment_code)
+                .unwrap_or_else(|e| panic!("compile solidity error: {}", e));
+            let report = evm_verify(deployment_code, &artifact.instances, &artifact.proof)
+                .unwrap_or_else(|e| panic!("evm verify error: {}", e));
+            if let Some(reason) = report.revert_reason {
+                panic!("evm verify proof error: {}", reason);
+            }
+            println!("deployment gas cost: {}", report.deploy_gas);
+            println!("verified gas cost: {}", report.call_gas);
+            println!("calldata size: {}", report.calldata_size);
+            println!("code size: {}", report.code_size);
+        }
+
+        artifact
+            .write(&self.file)
+            .unwrap_or_else(|e| panic!("write proof file [{}] error: {}", self.file, e));
+    }
+}
+
+enum ProofInput {
+    Artifact(ProofArtifact),
+    Raw {
+        multi_open: MultiOpen,
+        transcript: Transcript,
+        instances: Vec<Vec<Fr>>,
+        proof: Vec<u8>,
+    },
+    Calldata {
+        multi_open: MultiOpen,
+        calldata: Vec<u8>,
+    },
+}
+
+struct VerifyCommand {
+    params: String,
+    vk: String,
+    format: SerdeFormat,
+    input: ProofInput,
+    args: CircuitArgs,
+}
+
+impl CircuitVisitor for VerifyCommand {
+    type Output = ();
+
+    fn visit<C: RegisteredCircuit>(self) {
+        let params = read_params(self.params);
+        let vk = read_vk::<C>(&self.vk, self.format)
+            .unwrap_or_else(|e| panic!("read vk file [{}] error: {}", self.vk, e));
+        let params = fit_params(params, vk.get_domain().k());
+
+        let artifact = match self.input {
+            ProofInput::Artifact(artifact) => artifact,
+            ProofInput::Raw {
+                multi_open,
+                transcript,
+                instances,
+                proof,
+            } => ProofArtifact::new(C::NAME, &vk, multi_open, transcript, instances, proof),
+            ProofInput::Calldata {
+                multi_open,
+                calldata,
+            } => {
+                let num_instance = C::empty(&self.args)
+                    .unwrap_or_else(usage_error)
+                    .num_instance();
+                let (instances, proof) = decode_calldata(&calldata, &num_instance)
+                    .unwrap_or_else(|e| panic!("decode calldata error: {}", e));
+                for (column, instances) in instances.iter().enumerate() {
+                    for (row, instance) in instances.iter().enumerate() {
+                        println!("instance[{}][{}]: {}", column, row, instance.to_hex());
+                    }
+                }
+                println!("proof size: {}", proof.len());
+                ProofArtifact::new(C::NAME, &vk, multi_open, Transcript::Evm, instances, proof)
+            }
+        };
+
+        match artifact.verify(&params, &vk) {
+            Ok(true) => println!("Verify proof result: true"),
+            Ok(false) => {
+                println!("Verify proof result: false");
+                process::exit(1);
+            }
+            Err(e) => {
+                println!("Verify proof result: false ({})", e);
+                process::exit(1);
+            }
         }
     }
 }
@@ -1,4 +1,11 @@
-use clap::{Parser, Subcommand};
+use clap::{builder::PossibleValuesParser, ArgGroup, Parser, Subcommand, ValueEnum};
+use halo2_curves::bn256::Fr;
+use halo2_proofs::SerdeFormat;
+
+use crate::{
+    circuits::{circuit_names, gadgets::Field},
+    generator::{MultiOpen, Transcript},
+};
 
 #[derive(Debug, Parser)]
 #[clap(name = "evm-generator", version = "0.1.0")]
@@ -16,15 +23,82 @@ pub struct Opts {
 pub enum Subcommands {
     #[clap(name = "params")]
     #[clap(about = "Generate KZG params (don't used in production).")]
+    #[clap(args_conflicts_with_subcommands = true)]
     Params {
+        #[clap(subcommand)]
+        action: Option<ParamsAction>,
         #[clap(long, short, value_name = "file", default_value = "output/params.bin")]
         file: String,
+        /// Defaults to the smallest k which holds the circuit
+        #[clap(short, value_name = "k")]
+        k: Option<u32>,
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
+    },
+
+    #[clap(name = "keygen")]
+    #[clap(about = "Generate proving and verifying keys for circuit.")]
+    Keygen {
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
+        #[clap(
+            long,
+            short,
+            value_name = "params",
+            default_value = "output/params.bin"
+        )]
+        params: String,
+        #[clap(long, value_name = "vk", default_value = "output/vk.bin")]
+        vk: String,
+        #[clap(long, value_name = "pk", default_value = "output/pk.bin")]
+        pk: String,
+        #[clap(long, value_enum, value_name = "format", default_value = "processed")]
+        format: KeyFormat,
+        #[clap(
+            long,
+            short,
+            value_name = "constant",
+            default_value = "7",
+            value_parser = Fr::parse
+        )]
+        constant: Fr,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
     },
 
     #[clap(name = "solidity")]
     #[clap(visible_aliases = &["sol"])]
     #[clap(about = "Generate verifier solidity contract.")]
     Solidity {
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
         #[clap(
             long,
             short,
@@ -39,28 +113,88 @@ pub enum Subcommands {
             default_value = "output/params.bin"
         )]
         params: String,
-        #[clap(long, short, value_name = "constant", default_value = "7")]
-        constant: u64,
+        #[clap(long, value_name = "vk", default_value = "output/vk.bin")]
+        vk: String,
+        #[clap(long, value_enum, value_name = "format", default_value = "processed")]
+        format: KeyFormat,
+        #[clap(long, value_enum, value_name = "scheme", default_value = "gwc")]
+        multi_open: MultiOpen,
         #[clap(long, short, value_name = "bytecode")]
         bytecode: bool,
+        /// Also write a typed `verify(uint256[] instances, bytes proof)` wrapper contract
+        #[clap(long, value_name = "wrapper")]
+        wrapper: Option<String>,
+        /// Also write a Hardhat/Foundry JSON artifact with abi, bytecode and circuit metadata
+        #[clap(long, value_name = "artifact")]
+        artifact: Option<String>,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
+    },
+
+    #[clap(name = "scaffold")]
+    #[clap(about = "Generate a Foundry project with the verifier, wrapper and tests of a proof.")]
+    Scaffold {
+        #[clap(long, short, value_name = "dir", default_value = "output/foundry")]
+        dir: String,
+        #[clap(long, short, value_name = "proof", default_value = "output/proof.json")]
+        proof: String,
+        #[clap(long, value_name = "params", default_value = "output/params.bin")]
+        params: String,
+        #[clap(long, value_name = "vk", default_value = "output/vk.bin")]
+        vk: String,
+        #[clap(long, value_enum, value_name = "format", default_value = "processed")]
+        format: KeyFormat,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
     },
 
     #[clap(name = "graph")]
     #[clap(visible_aliases = &["gp"])]
     #[clap(about = "Generate circuit layout graph.")]
     Graph {
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
         #[clap(long, short, value_name = "file", default_value = "output/graph.png")]
         file: String,
-        #[clap(long, short, value_name = "title", default_value = "Simple circuit")]
-        title: String,
-        #[clap(short, value_name = "k", default_value = "5")]
-        k: u32,
+        #[clap(long, short, value_name = "title")]
+        title: Option<String>,
+        #[clap(short, value_name = "k")]
+        k: Option<u32>,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
     },
 
     #[clap(name = "proof")]
     #[clap(visible_aliases = &["pro"])]
     #[clap(about = "Generate proof for circuit.")]
     Proof {
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
         #[clap(long, short, value_name = "file", default_value = "output/proof.json")]
         file: String,
         #[clap(long, short)]
@@ -72,17 +206,93 @@ pub enum Subcommands {
             default_value = "output/params.bin"
         )]
         params: String,
-        #[clap(long, short, value_name = "constant", default_value = "7")]
-        constant: u64,
-        #[clap(short, default_value = "3")]
-        a: u64,
-        #[clap(short, default_value = "5")]
-        b: u64,
+        #[clap(long, value_name = "pk", default_value = "output/pk.bin")]
+        pk: String,
+        #[clap(long, value_enum, value_name = "format", default_value = "processed")]
+        format: KeyFormat,
+        #[clap(long, value_enum, value_name = "scheme", default_value = "gwc")]
+        multi_open: MultiOpen,
+        /// Only proofs with the evm transcript can be verified on-chain
+        #[clap(long, value_enum, value_name = "transcript", default_value = "evm")]
+        transcript: Transcript,
+        #[clap(
+            long,
+            short,
+            value_name = "constant",
+            default_value = "7",
+            value_parser = Fr::parse
+        )]
+        constant: Fr,
+        /// JSON witness file with the private inputs of the circuit, defaults are used if omitted
+        #[clap(long, value_name = "inputs")]
+        inputs: Option<String>,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
+    },
+
+    #[clap(name = "stats")]
+    #[clap(about = "Show columns, gates, lookups, rows and minimum k of circuits.")]
+    Stats {
+        /// All registered circuits are shown if omitted
+        #[clap(
+            long,
+            value_name = "circuit",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: Option<String>,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
+    },
+
+    #[clap(name = "check")]
+    #[clap(about = "Check circuit witness with the mock prover before keygen and proving.")]
+    Check {
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
+        #[clap(short, value_name = "k")]
+        k: Option<u32>,
+        #[clap(
+            long,
+            short,
+            value_name = "constant",
+            default_value = "7",
+            value_parser = Fr::parse
+        )]
+        constant: Fr,
+        /// JSON witness file with the private inputs of the circuit, defaults are used if omitted
+        #[clap(long, value_name = "inputs")]
+        inputs: Option<String>,
+        /// JSON file with the expected instances, the ones computed from the inputs if omitted
+        #[clap(long, value_name = "instances")]
+        instances: Option<String>,
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
     },
 
     #[clap(name = "verify")]
     #[clap(visible_aliases = &["ver"])]
     #[clap(about = "Verify proof for circuit.")]
+    #[clap(group = ArgGroup::new("raw").args(["instances", "calldata"]))]
     Verify {
         #[clap(
             long,
@@ -91,11 +301,171 @@ pub enum Subcommands {
             default_value = "output/params.bin"
         )]
         params: String,
-        #[clap(long, short, value_name = "constant", default_value = "7")]
-        constant: u64,
-        #[clap(short, default_value = "1575")]
-        c: u64,
-        #[clap(long, short, value_name = "proof")]
+        #[clap(long, value_name = "vk", default_value = "output/vk.bin")]
+        vk: String,
+        #[clap(long, value_enum, value_name = "format", default_value = "processed")]
+        format: KeyFormat,
+        /// Proof artifact, or the raw proof (hex or binary) when `--instances` is given
+        #[clap(long, value_name = "proof", default_value = "output/proof.json")]
         proof: String,
+        /// JSON file with an array of decimal or hex field elements per instance column
+        #[clap(long, value_name = "instances")]
+        instances: Option<String>,
+        /// `0x` prefixed calldata of the verifier contract, or a file holding it, instead of
+        /// a proof
+        #[clap(long, value_name = "calldata")]
+        calldata: Option<String>,
+        #[clap(
+            long,
+            value_name = "circuit",
+            default_value = "simple",
+            requires = "raw",
+            value_parser = PossibleValuesParser::new(circuit_names())
+        )]
+        circuit: String,
+        #[clap(
+            long,
+            value_enum,
+            value_name = "scheme",
+            default_value = "gwc",
+            requires = "raw"
+        )]
+        multi_open: MultiOpen,
+        #[clap(
+            long,
+            value_enum,
+            value_name = "transcript",
+            default_value = "evm",
+            requires = "instances",
+            conflicts_with = "calldata"
+        )]
+        transcript: Transcript,
+        #[clap(
+            long,
+            short,
+            value_name = "constant",
+            default_value = "7",
+            requires = "raw",
+            value_parser = Fr::parse
+        )]
+        constant: Fr,
+        /// Circuit arguments, needed to split calldata into instances and proof
+        #[clap(
+            long = "arg",
+            short = 'x',
+            value_name = "name=value",
+            requires = "raw",
+            value_parser = parse_arg
+        )]
+        args: Vec<(String, Fr)>,
     },
 }
+
+#[derive(Debug, Subcommand)]
+pub enum ParamsAction {
+    #[clap(name = "downsize")]
+    #[clap(about = "Derive smaller KZG params from a larger params file.")]
+    Downsize {
+        #[clap(long, short, value_name = "input")]
+        input: String,
+        #[clap(long, short, value_name = "file", default_value = "output/params.bin")]
+        file: String,
+        #[clap(short, value_name = "k")]
+        k: u32,
+    },
+
+    #[clap(name = "contribute")]
+    #[clap(about = "Mix fresh randomness into KZG params as a ceremony contribution.")]
+    Contribute {
+        #[clap(long, short, value_name = "input")]
+        input: String,
+        #[clap(long, short, value_name = "file")]
+        file: String,
+        /// Proof of the contribution, with a proof of knowledge of its secret bound to the
+        /// input params
+        #[clap(
+            long,
+            short,
+            value_name = "contribution",
+            default_value = "output/contribution.bin"
+        )]
+        contribution: String,
+    },
+
+    #[clap(name = "verify-ceremony")]
+    #[clap(about = "Verify a chain of ceremony params and their contributions.")]
+    VerifyCeremony {
+        #[clap(long, short, value_name = "params", num_args = 2.., required = true)]
+        params: Vec<String>,
+        #[clap(long, short, value_name = "contribution", num_args = 1.., required = true)]
+        contributions: Vec<String>,
+    },
+
+    #[clap(name = "import")]
+    #[clap(about = "Import KZG params from a .ptau or powers-of-tau challenge file.")]
+    Import {
+        #[clap(long, short, value_name = "input")]
+        input: String,
+        #[clap(long, short, value_name = "file", default_value = "output/params.bin")]
+        file: String,
+        #[clap(short, value_name = "k")]
+        k: u32,
+    },
+}
+
+/// Serialization format of proving and verifying key files.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum KeyFormat {
+    /// Compressed curve points, smallest files but slowest to read.
+    Processed,
+    /// Raw field limbs with curve checks on read.
+    RawBytes,
+    /// Raw field limbs without any checks, only for trusted files.
+    RawBytesUnchecked,
+}
+
+impl From<KeyFormat> for SerdeFormat {
+    fn from(format: KeyFormat) -> Self {
+        match format {
+            KeyFormat::Processed => SerdeFormat::Processed,
+            KeyFormat::RawBytes => SerdeFormat::RawBytes,
+            KeyFormat::RawBytesUnchecked => SerdeFormat::RawBytesUnchecked,
+        }
+    }
+}
+
+/// Parses a circuit argument given as `name=value`.
+fn parse_arg(s: &str) -> Result<(String, Fr), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid argument [{}], expect name=value", s))?;
+    let value =
+        Fr::parse(value).map_err(|e| format!("invalid value of argument [{}]: {}", name, e))?;
+    Ok((name.to_string(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, Parser};
+
+    use super::Opts;
+
+    #[test]
+    fn command() {
+        Opts::command().debug_assert();
+    }
+
+    #[test]
+    fn verify_calldata() {
+        let parse = |args: &[&str]| {
+            Opts::try_parse_from(
+                ["evm-generator", "verify"]
+                    .into_iter()
+                    .chain(args.iter().copied()),
+            )
+        };
+        assert!(parse(&["--calldata", "0x00"]).is_ok());
+        assert!(parse(&["--instances", "instances.json", "--transcript", "poseidon"]).is_ok());
+        assert!(parse(&["--calldata", "0x00", "--transcript", "poseidon"]).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use halo2_curves::{
+    bn256::{Bn256, Fr, G1Affine},
+    ff::PrimeField,
+};
+use halo2_proofs::{
+    plonk::VerifyingKey,
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use serde::{Deserialize, Serialize};
+use snark_verifier::loader::evm::encode_calldata;
+
+use crate::{
+    circuits::inputs::field,
+    generator::{downsize_srs, verify_proof, Error, MultiOpen, Transcript},
+};
+
+/// Version of the proof artifact format written by this crate.
+pub const PROOF_ARTIFACT_VERSION: u32 = 1;
+
+/// Proof with everything needed to verify it natively or on-chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofArtifact {
+    pub version: u32,
+    /// Name of the registered circuit the proof is for.
+    pub circuit: String,
+    pub k: u32,
+    pub multi_open: MultiOpen,
+    /// Artifacts written before the transcript was recorded always used the EVM transcript.
+    #[serde(default)]
+    pub transcript: Transcript,
+    /// Transcript hash of the verifying key the proof was created with.
+    #[serde(with = "field")]
+    pub vk_hash: Fr,
+    /// Public instances of each instance column.
+    #[serde(with = "hex_instances")]
+    pub instances: Vec<Vec<Fr>>,
+    #[serde(with = "hex_bytes")]
+    pub proof: Vec<u8>,
+    /// Instances and proof encoded as the generated verifier contract expects, which can only
+    /// verify proofs with the EVM transcript.
+    #[serde(with = "hex_bytes")]
+    pub calldata: Vec<u8>,
+}
+
+impl ProofArtifact {
+    pub fn new(
+        circuit: &str,
+        vk: &VerifyingKey<G1Affine>,
+        multi_open: MultiOpen,
+        transcript: Transcript,
+        instances: Vec<Vec<Fr>>,
+        proof: Vec<u8>,
+    ) -> Self {
+        let calldata = encode_calldata(&instances, &proof);
+        Self {
+            version: PROOF_ARTIFACT_VERSION,
+            circuit: circuit.to_string(),
+            k: vk.get_domain().k(),
+            multi_open,
+            transcript,
+            vk_hash: vk.transcript_repr(),
+            instances,
+            proof,
+            calldata,
+        }
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let reader = BufReader::new(File::open(path)?);
+        let artifact: Self = serde_json::from_reader(reader)?;
+        if artifact.version != PROOF_ARTIFACT_VERSION {
+            return Err(Error::UnsupportedVersion(artifact.version));
+        }
+        Ok(artifact)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, self)?;
+        Ok(writer.flush()?)
+    }
+
+    /// Verifies the proof natively, `vk` must be the key the proof was created with. Larger
+    /// `params` are downsized to the domain of `vk`, which the verifier relies on.
+    pub fn verify(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        vk: &VerifyingKey<G1Affine>,
+    ) -> Result<bool, Error> {
+        let k = vk.get_domain().k();
+        if vk.transcript_repr() != self.vk_hash || self.k != k {
+            return Ok(false);
+        }
+        let downsized;
+        let params = match params.k() > k {
+            true => {
+                downsized = downsize_srs(params, k)?;
+                &downsized
+            }
+            false => params,
+        };
+        verify_proof(
+            params,
+            vk,
+            self.proof.clone(),
+            &self.instances,
+            self.multi_open,
+            self.transcript,
+        )
+    }
+}
+
+/// Instance value given as a number or a string accepted by
+/// [`Field::parse`](crate::circuits::gadgets::Field::parse).
+#[derive(Deserialize)]
+struct Instance(#[serde(with = "field")] Fr);
+
+/// Reads public instances from a JSON file holding one array per instance column, each
+/// value a number or a string accepted by
+/// [`Field::parse`](crate::circuits::gadgets::Field::parse).
+pub fn read_instances(path: impl AsRef<Path>) -> Result<Vec<Vec<Fr>>, Error> {
+    let reader = BufReader::new(File::open(path)?);
+    let columns: Vec<Vec<Instance>> = serde_json::from_reader(reader)?;
+    Ok(columns
+        .into_iter()
+        .map(|column| column.into_iter().map(|Instance(value)| value).collect())
+        .collect())
+}
+
+/// Reads a proof file holding either `0x` prefixed hex or raw bytes.
+pub fn read_proof(path: impl AsRef<Path>) -> Result<Vec<u8>, Error> {
+    let bytes = fs::read(path)?;
+    match std::str::from_utf8(&bytes).map(|s| s.trim().strip_prefix("0x")) {
+        Ok(Some(s)) => Ok(hex::decode(s)?),
+        _ => Ok(bytes),
+    }
+}
+
+/// Splits calldata of the generated verifier contract back into the instances, with
+/// `num_instance` instances in each column, and the proof.
+pub fn decode_calldata(
+    calldata: &[u8],
+    num_instance: &[usize],
+) -> Result<(Vec<Vec<Fr>>, Vec<u8>), Error> {
+    let len = num_instance.iter().sum::<usize>() * 32;
+    if calldata.len() < len {
+        return Err(Error::InvalidCalldata(format!(
+            "expect at least {} bytes of instances, got {} bytes",
+            len,
+            calldata.len()
+        )));
+    }
+    let (mut words, proof) = calldata.split_at(len);
+
+    let mut instances = Vec::with_capacity(num_instance.len());
+    for num_instance in num_instance {
+        let mut column = Vec::with_capacity(*num_instance);
+        for _ in 0..*num_instance {
+            let mut repr = [0u8; 32];
+            repr.copy_from_slice(&words[..32]);
+            repr.reverse();
+            let instance = Option::from(Fr::from_repr(repr)).ok_or_else(|| {
+                Error::InvalidCalldata(format!("0x{} is out of field", hex::encode(&words[..32])))
+            })?;
+            column.push(instance);
+            words = &words[32..];
+        }
+        instances.push(column);
+    }
+
+    Ok((instances, proof.to_vec()))
+}
+
+/// `0x` prefixed hex of bytes.
+pub(crate) mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(D::Error::custom)
+    }
+}
+
+mod hex_instances {
+    use halo2_curves::bn256::Fr;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::circuits::gadgets::Field;
+
+    pub fn serialize<S: Serializer>(
+        instances: &[Vec<Fr>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        instances
+            .iter()
+            .map(|column| column.iter().map(Field::to_hex).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Vec<Fr>>, D::Error> {
+        Vec::<Vec<String>>::deserialize(deserializer)?
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .map(|value| Fr::parse(value).map_err(D::Error::custom))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use halo2_curves::{bn256::Fr, ff::Field};
+    use halo2_proofs::circuit::Value;
+
+    use super::{decode_calldata, read_instances, read_proof, ProofArtifact};
+    use crate::{
+        circuits::simple::SimpleCircuit,
+        generator::{downsize_srs, gen_pk, gen_proof, gen_srs, Error, MultiOpen, Transcript},
+    };
+
+    #[test]
+    fn round_trip() {
+        let larger = gen_srs(5).unwrap();
+        let params = downsize_srs(&larger, 4).unwrap();
+        let constant = Fr::from(7);
+        let pk = gen_pk(
+            &params,
+            &SimpleCircuit {
+                constant,
+                a: Value::unknown(),
+                b: Value::unknown(),
+            },
+        )
+        .unwrap();
+
+        let (a, b) = (Fr::from(3), Fr::from(5));
+        let circuit = SimpleCircuit {
+            constant,
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        let instances = vec![vec![constant * a.square() * b.square()]];
+        let (multi_open, transcript) = (MultiOpen::Shplonk, Transcript::Poseidon);
+        let proof = gen_proof(&params, &pk, circuit, &instances, multi_open, transcript).unwrap();
+
+        let artifact = ProofArtifact::new(
+            "simple",
+            pk.get_vk(),
+            multi_open,
+            transcript,
+            instances,
+            proof,
+        );
+        let file = env::temp_dir().join("halo2-evm-verifier-test-proof.json");
+        artifact.write(&file).unwrap();
+        let artifact = ProofArtifact::read(&file).unwrap();
+        assert_eq!(artifact.k, 4);
+        assert_eq!(artifact.transcript, Transcript::Poseidon);
+        assert!(artifact.verify(&params, pk.get_vk()).unwrap());
+        assert!(artifact.verify(&larger, pk.get_vk()).unwrap());
+
+        let mut resized = artifact.clone();
+        resized.k = 5;
+        assert!(!resized.verify(&larger, pk.get_vk()).unwrap());
+
+        let file = env::temp_dir().join("halo2-evm-verifier-test-instances.json");
+        fs::write(&file, format!(r#"[["{}"]]"#, 1575)).unwrap();
+        assert_eq!(read_instances(&file).unwrap(), artifact.instances);
+        fs::write(&file, format!(r#"[["0x{:x}"]]"#, 1575)).unwrap();
+        assert_eq!(read_instances(&file).unwrap(), artifact.instances);
+        fs::write(&file, format!(r#"[[{}]]"#, 1575)).unwrap();
+        assert_eq!(read_instances(&file).unwrap(), artifact.instances);
+        fs::write(&file, r#"[["1575a"]]"#).unwrap();
+        assert!(read_instances(&file).is_err());
+
+        let file = env::temp_dir().join("halo2-evm-verifier-test-raw-proof.txt");
+        fs::write(&file, format!("0x{}", hex::encode(&artifact.proof))).unwrap();
+        assert_eq!(read_proof(&file).unwrap(), artifact.proof);
+        fs::write(&file, "0x12g4").unwrap();
+        assert!(matches!(read_proof(&file), Err(Error::Hex(_))));
+
+        let calldata = decode_calldata(&artifact.calldata, &[1]).unwrap();
+        assert_eq!(
+            calldata,
+            (artifact.instances.clone(), artifact.proof.clone())
+        );
+        assert!(decode_calldata(&artifact.calldata[..31], &[1]).is_err());
+        assert!(decode_calldata(&[0xff; 32], &[1]).is_err());
+
+        let mut tampered = artifact;
+        tampered.instances[0][0] += Fr::from(1);
+        assert!(!tampered.verify(&params, pk.get_vk()).unwrap_or(false));
+    }
+}
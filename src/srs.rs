@@ -0,0 +1,619 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use halo2_curves::{
+    bn256::{Bn256, Fq, Fq2, Fr, G1Affine, G2Affine, G1},
+    ff::{Field, FromUniformBytes, PrimeField},
+    group::{prime::PrimeCurveAffine, Curve},
+    pairing::Engine,
+    serde::SerdeObject,
+    CurveAffine,
+};
+use halo2_proofs::{
+    arithmetic::{best_multiexp, g_to_lagrange},
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+};
+use rand::{rngs::OsRng, RngCore};
+use sha3::{Digest, Keccak256, Keccak512};
+
+use crate::generator::Error;
+
+/// Base field modulus of BN254 in big-endian hex, checked against `.ptau` headers.
+const BN254_Q: &str = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47";
+
+/// Size of the blake2b hash of the previous contribution heading a challenge file.
+const CHALLENGE_HASH_SIZE: u64 = 64;
+
+const G1_SIZE: u64 = 64;
+const G2_SIZE: u64 = 128;
+
+/// Imports params of size `k` from a snarkjs `.ptau` file or a PSE/Hermez
+/// powers-of-tau challenge file, detected by the `ptau` magic.
+pub fn import_srs(path: impl AsRef<Path>, k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+    let mut magic = [0u8; 4];
+    File::open(path.as_ref())?.read_exact(&mut magic)?;
+    if magic == *b"ptau" {
+        import_ptau(path, k)
+    } else {
+        import_challenge(path, k)
+    }
+}
+
+/// Imports params of size `k` from a snarkjs `.ptau` file.
+///
+/// Points are stored uncompressed with coordinates as little-endian Montgomery limbs,
+/// the same layout as `SerdeObject` raw bytes.
+pub fn import_ptau(path: impl AsRef<Path>, k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != *b"ptau" {
+        return Err(Error::Srs(String::from("missing ptau magic")));
+    }
+    let _version = read_u32(&mut reader)?;
+    let num_sections = read_u32(&mut reader)?;
+
+    // section id -> (offset, size)
+    let mut sections = vec![None; 16];
+    for _ in 0..num_sections {
+        let id = read_u32(&mut reader)? as usize;
+        let size = read_u64(&mut reader)?;
+        let offset = reader.stream_position()?;
+        if id < sections.len() {
+            sections[id] = Some((offset, size));
+        }
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let section =
+        |id: usize| sections[id].ok_or_else(|| Error::Srs(format!("missing ptau section {}", id)));
+
+    let (header, _) = section(1)?;
+    reader.seek(SeekFrom::Start(header))?;
+    let n8 = read_u32(&mut reader)?;
+    let mut q = vec![0u8; n8 as usize];
+    reader.read_exact(&mut q)?;
+    q.reverse();
+    if hex::encode(q) != BN254_Q {
+        return Err(Error::Srs(String::from("ptau file is not for BN254")));
+    }
+    let power = read_u32(&mut reader)?;
+    if k == 0 || k > power {
+        return Err(Error::InvalidK(k));
+    }
+
+    let (tau_g1, _) = section(2)?;
+    reader.seek(SeekFrom::Start(tau_g1))?;
+    let g = (0..1u64 << k)
+        .map(|_| {
+            let x = read_fq_lem(&mut reader)?;
+            let y = read_fq_lem(&mut reader)?;
+            g1_from_xy(x, y)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let (tau_g2, _) = section(3)?;
+    reader.seek(SeekFrom::Start(tau_g2))?;
+    let mut g2 = [G2Affine::identity(); 2];
+    for point in g2.iter_mut() {
+        let x = Fq2 {
+            c0: read_fq_lem(&mut reader)?,
+            c1: read_fq_lem(&mut reader)?,
+        };
+        let y = Fq2 {
+            c0: read_fq_lem(&mut reader)?,
+            c1: read_fq_lem(&mut reader)?,
+        };
+        *point = g2_from_xy(x, y)?;
+    }
+
+    params_from_powers(k, g, g2[0], g2[1])
+}
+
+/// Imports params of size `k` from a PSE/Hermez powers-of-tau challenge file.
+///
+/// Points are stored uncompressed with big-endian coordinates, `Fq2` elements as `c1, c0`.
+pub fn import_challenge(path: impl AsRef<Path>, k: u32) -> Result<ParamsKZG<Bn256>, Error> {
+    let file = File::open(path)?;
+    let power = challenge_power(file.metadata()?.len())
+        .ok_or_else(|| Error::Srs(String::from("unexpected challenge file size")))?;
+    if k == 0 || k > power {
+        return Err(Error::InvalidK(k));
+    }
+    let mut reader = BufReader::new(file);
+
+    reader.seek(SeekFrom::Start(CHALLENGE_HASH_SIZE))?;
+    let g = (0..1u64 << k)
+        .map(|_| {
+            let mut bytes = [0u8; G1_SIZE as usize];
+            reader.read_exact(&mut bytes)?;
+            if bytes[0] & 0x40 != 0 {
+                return Ok(G1Affine::identity());
+            }
+            bytes[0] &= 0x3f;
+            g1_from_xy(fq_from_be(&bytes[..32])?, fq_from_be(&bytes[32..])?)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let tau_g2 = CHALLENGE_HASH_SIZE + ((2u64 << power) - 1) * G1_SIZE;
+    reader.seek(SeekFrom::Start(tau_g2))?;
+    let mut g2 = [G2Affine::identity(); 2];
+    for point in g2.iter_mut() {
+        let mut bytes = [0u8; G2_SIZE as usize];
+        reader.read_exact(&mut bytes)?;
+        bytes[0] &= 0x3f;
+        let x = Fq2 {
+            c1: fq_from_be(&bytes[..32])?,
+            c0: fq_from_be(&bytes[32..64])?,
+        };
+        let y = Fq2 {
+            c1: fq_from_be(&bytes[64..96])?,
+            c0: fq_from_be(&bytes[96..])?,
+        };
+        *point = g2_from_xy(x, y)?;
+    }
+
+    params_from_powers(k, g, g2[0], g2[1])
+}
+
+/// Builds params from the monomial powers `[s^i]G1`, `G2` and `[s]G2`, checking they
+/// are consecutive powers of the same secret `s`.
+pub fn params_from_powers(
+    k: u32,
+    g: Vec<G1Affine>,
+    g2: G2Affine,
+    s_g2: G2Affine,
+) -> Result<ParamsKZG<Bn256>, Error> {
+    if k == 0 {
+        return Err(Error::InvalidK(k));
+    }
+    if g.len() != 1 << k {
+        return Err(Error::Srs(format!(
+            "expect {} G1 powers, got {}",
+            1 << k,
+            g.len()
+        )));
+    }
+    if g[0] != G1Affine::generator() || g2 != G2Affine::generator() {
+        return Err(Error::Srs(String::from(
+            "powers don't start at the generators",
+        )));
+    }
+    if Bn256::pairing(&g[1], &g2) != Bn256::pairing(&g[0], &s_g2) {
+        return Err(Error::Srs(String::from("[s]G1 and [s]G2 don't match")));
+    }
+
+    // e(sum r_i [s^(i+1)]G1, G2) == e(sum r_i [s^i]G1, [s]G2) for random r_i
+    let r = (0..g.len() - 1)
+        .map(|_| Fr::random(OsRng))
+        .collect::<Vec<_>>();
+    let lhs: G1Affine = best_multiexp(&r, &g[1..]).into();
+    let rhs: G1Affine = best_multiexp(&r, &g[..g.len() - 1]).into();
+    if Bn256::pairing(&lhs, &g2) != Bn256::pairing(&rhs, &s_g2) {
+        return Err(Error::Srs(String::from(
+            "G1 elements are not consecutive powers",
+        )));
+    }
+
+    let g_lagrange: Vec<G1Affine> = g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k);
+
+    // assemble the raw bytes layout of `ParamsKZG::write` and read it back
+    let mut buf = Vec::with_capacity(4 + (g.len() * 2) * G1_SIZE as usize + 2 * G2_SIZE as usize);
+    buf.extend_from_slice(&k.to_le_bytes());
+    for point in g.iter().chain(g_lagrange.iter()) {
+        point.write_raw(&mut buf)?;
+    }
+    g2.write_raw(&mut buf)?;
+    s_g2.write_raw(&mut buf)?;
+
+    let params = ParamsKZG::<Bn256>::read(&mut buf.as_slice()).map_err(Error::Serde)?;
+    debug_assert_eq!(params.k(), k);
+    Ok(params)
+}
+
+/// Proof that params were derived from the previous params by mixing in a secret `t`.
+///
+/// The pairing checks only show the new powers are the previous ones scaled by `t`, the
+/// Schnorr proof `(r_g1, z)` shows the contributor knows `t` and binds it to the hash of
+/// the previous params, so a contribution can't be derived from earlier powers or replayed
+/// on other params.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contribution {
+    /// Hash of the params the contribution was applied to.
+    pub previous: [u8; 32],
+    /// Hash of the params the contribution produced.
+    pub next: [u8; 32],
+    /// `[t]G1`.
+    pub t_g1: G1Affine,
+    /// `[t]G2`.
+    pub t_g2: G2Affine,
+    /// Schnorr commitment `[r]G1` of a random nonce `r`.
+    pub r_g1: G1Affine,
+    /// Schnorr response `r + c * t`, with the challenge `c` from [`Contribution::challenge`].
+    pub z: Fr,
+}
+
+impl Contribution {
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Error> {
+        writer.write_all(&self.previous)?;
+        writer.write_all(&self.next)?;
+        self.t_g1.write_raw(writer)?;
+        self.t_g2.write_raw(writer)?;
+        self.r_g1.write_raw(writer)?;
+        writer.write_all(&self.z.to_repr())?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, Error> {
+        let mut previous = [0u8; 32];
+        reader.read_exact(&mut previous)?;
+        let mut next = [0u8; 32];
+        reader.read_exact(&mut next)?;
+        let t_g1 = G1Affine::read_raw(reader).map_err(Error::Serde)?;
+        let t_g2 = G2Affine::read_raw(reader).map_err(Error::Serde)?;
+        let r_g1 = G1Affine::read_raw(reader).map_err(Error::Serde)?;
+        let mut repr = [0u8; 32];
+        reader.read_exact(&mut repr)?;
+        let z = Option::from(Fr::from_repr(repr))
+            .ok_or_else(|| Error::Srs(String::from("invalid field element")))?;
+        Ok(Self {
+            previous,
+            next,
+            t_g1,
+            t_g2,
+            r_g1,
+            z,
+        })
+    }
+
+    /// Fiat-Shamir challenge of the Schnorr proof, a hash of the previous params hash,
+    /// `[t]G1` and `[r]G1`.
+    pub fn challenge(previous: &[u8; 32], t_g1: &G1Affine, r_g1: &G1Affine) -> Fr {
+        let mut hasher = Keccak512::new();
+        hasher.update(b"halo2-evm-verifier contribution");
+        hasher.update(previous);
+        hasher.update(t_g1.to_raw_bytes());
+        hasher.update(r_g1.to_raw_bytes());
+        let mut bytes = [0u8; 64];
+        bytes.copy_from_slice(&hasher.finalize());
+        Fr::from_uniform_bytes(&bytes)
+    }
+
+    /// Checks the Schnorr proof `[z]G1 == [r]G1 + [c]([t]G1)`.
+    fn verify_knowledge(&self) -> bool {
+        let c = Self::challenge(&self.previous, &self.t_g1, &self.r_g1);
+        G1Affine::generator() * self.z == self.r_g1 + self.t_g1 * c
+    }
+}
+
+/// Keccak256 of the serialized params, identifying them in a ceremony.
+pub fn params_hash(params: &ParamsKZG<Bn256>) -> Result<[u8; 32], Error> {
+    let mut hasher = HashWriter(Keccak256::new());
+    params.write(&mut hasher)?;
+    Ok(hasher.0.finalize().into())
+}
+
+/// Mixes fresh randomness into `params`, returning the new params and the proof of
+/// the contribution. The secret is dropped once this returns.
+pub fn contribute(
+    params: &ParamsKZG<Bn256>,
+    mut rng: impl RngCore,
+) -> Result<(ParamsKZG<Bn256>, Contribution), Error> {
+    let t = Fr::random(&mut rng);
+
+    // [s^i]G1 -> [(s * t)^i]G1
+    let mut power = Fr::ONE;
+    let g = params
+        .get_g()
+        .iter()
+        .map(|g| {
+            let g = *g * power;
+            power *= t;
+            g
+        })
+        .collect::<Vec<G1>>();
+    let mut g_affine = vec![G1Affine::identity(); g.len()];
+    G1::batch_normalize(&g, &mut g_affine);
+    let s_g2 = (params.s_g2() * t).into();
+
+    let next = params_from_powers(params.k(), g_affine, params.g2(), s_g2)?;
+    let previous = params_hash(params)?;
+    let t_g1 = (G1Affine::generator() * t).into();
+    let r = Fr::random(&mut rng);
+    let r_g1 = (G1Affine::generator() * r).into();
+    let c = Contribution::challenge(&previous, &t_g1, &r_g1);
+    let contribution = Contribution {
+        previous,
+        next: params_hash(&next)?,
+        t_g1,
+        t_g2: (G2Affine::generator() * t).into(),
+        r_g1,
+        z: r + c * t,
+    };
+    Ok((next, contribution))
+}
+
+/// Checks a chain of ceremony params, where `contributions[i]` turned `params[i]`
+/// into `params[i + 1]`.
+pub fn verify_ceremony(
+    params: &[ParamsKZG<Bn256>],
+    contributions: &[Contribution],
+) -> Result<(), Error> {
+    if params.len() != contributions.len() + 1 {
+        return Err(Error::Srs(format!(
+            "expect one more params than contributions, got {} params and {} contributions",
+            params.len(),
+            contributions.len()
+        )));
+    }
+
+    let hashes = params
+        .iter()
+        .map(|params| {
+            verify_powers(params)?;
+            params_hash(params)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let (g1, g2) = (G1Affine::generator(), G2Affine::generator());
+    for (i, contribution) in contributions.iter().enumerate() {
+        let (previous, next) = (&params[i], &params[i + 1]);
+        let fail = |reason: &str| Err(Error::Srs(format!("contribution {}: {}", i, reason)));
+
+        if previous.k() != next.k() {
+            return fail("params size changed");
+        }
+        if contribution.previous != hashes[i] || contribution.next != hashes[i + 1] {
+            return fail("params hash mismatch");
+        }
+        if bool::from(contribution.t_g1.is_identity()) {
+            return fail("secret is zero");
+        }
+        if !contribution.verify_knowledge() {
+            return fail("no proof of knowledge of the secret");
+        }
+        if Bn256::pairing(&contribution.t_g1, &g2) != Bn256::pairing(&g1, &contribution.t_g2) {
+            return fail("[t]G1 and [t]G2 don't match");
+        }
+        if Bn256::pairing(&next.get_g()[1], &g2)
+            != Bn256::pairing(&previous.get_g()[1], &contribution.t_g2)
+        {
+            return fail("params are not the previous params mixed with the secret");
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks `params` hold consecutive powers of one secret, by rebuilding them with
+/// [`params_from_powers`], and that the lagrange basis was derived from them.
+fn verify_powers(params: &ParamsKZG<Bn256>) -> Result<(), Error> {
+    let g = params.get_g();
+    let rebuilt = params_from_powers(params.k(), g.to_vec(), params.g2(), params.s_g2())?;
+    if params_hash(&rebuilt)? != params_hash(params)? {
+        return Err(Error::Srs(String::from("lagrange basis mismatch")));
+    }
+    Ok(())
+}
+
+struct HashWriter(Keccak256);
+
+impl Write for HashWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Returns the power of a challenge file from its size, which holds `2^(p+1) - 1`
+/// tau G1 powers, `2^p` tau G2, alpha G1 and beta G1 powers, and beta G2.
+fn challenge_power(size: u64) -> Option<u32> {
+    (1..=28).find(|power| {
+        let n = 1u64 << power;
+        size == CHALLENGE_HASH_SIZE
+            + (2 * n - 1) * G1_SIZE
+            + n * G2_SIZE
+            + 2 * n * G1_SIZE
+            + G2_SIZE
+    })
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, Error> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64, Error> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_fq_lem<R: Read>(reader: &mut R) -> Result<Fq, Error> {
+    let mut bytes = [0u8; 32];
+    reader.read_exact(&mut bytes)?;
+    Fq::from_raw_bytes(&bytes).ok_or_else(|| Error::Srs(String::from("invalid field element")))
+}
+
+fn fq_from_be(bytes: &[u8]) -> Result<Fq, Error> {
+    let mut repr = [0u8; 32];
+    repr.copy_from_slice(bytes);
+    repr.reverse();
+    Option::from(Fq::from_repr(repr))
+        .ok_or_else(|| Error::Srs(String::from("invalid field element")))
+}
+
+fn g1_from_xy(x: Fq, y: Fq) -> Result<G1Affine, Error> {
+    if x == Fq::ZERO && y == Fq::ZERO {
+        return Ok(G1Affine::identity());
+    }
+    Option::from(G1Affine::from_xy(x, y))
+        .ok_or_else(|| Error::Srs(String::from("G1 point not on curve")))
+}
+
+fn g2_from_xy(x: Fq2, y: Fq2) -> Result<G2Affine, Error> {
+    Option::from(G2Affine::from_xy(x, y))
+        .ok_or_else(|| Error::Srs(String::from("G2 point not on curve")))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use halo2_curves::{
+        bn256::{Bn256, Fr, G1Affine, G2Affine},
+        ff::{Field, PrimeField},
+        group::prime::PrimeCurveAffine,
+        serde::SerdeObject,
+        CurveAffine,
+    };
+    use halo2_proofs::poly::{commitment::Params, kzg::commitment::ParamsKZG};
+
+    use rand::rngs::OsRng;
+
+    use super::{
+        contribute, import_srs, params_from_powers, verify_ceremony, Contribution, BN254_Q,
+    };
+    use crate::generator::Error;
+
+    const POWER: u32 = 3;
+
+    fn powers(tau: Fr) -> (Vec<G1Affine>, Vec<G2Affine>) {
+        let mut g1 = Vec::new();
+        let mut g2 = Vec::new();
+        let mut s = Fr::ONE;
+        for i in 0..(2u64 << POWER) - 1 {
+            g1.push((G1Affine::generator() * s).into());
+            if i < 1 << POWER {
+                g2.push((G2Affine::generator() * s).into());
+            }
+            s *= tau;
+        }
+        (g1, g2)
+    }
+
+    fn write_ptau(path: &Path, tau: Fr) {
+        let (g1, g2) = powers(tau);
+        let mut header = 32u32.to_le_bytes().to_vec();
+        let mut q = hex::decode(BN254_Q).unwrap();
+        q.reverse();
+        header.extend(q);
+        header.extend(POWER.to_le_bytes());
+        header.extend(POWER.to_le_bytes());
+        let tau_g1 = g1.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<_>>();
+        let tau_g2 = g2.iter().flat_map(|p| p.to_raw_bytes()).collect::<Vec<_>>();
+
+        let mut buf = b"ptau".to_vec();
+        buf.extend(1u32.to_le_bytes());
+        buf.extend(3u32.to_le_bytes());
+        for (id, section) in [header, tau_g1, tau_g2].iter().enumerate() {
+            buf.extend((id as u32 + 1).to_le_bytes());
+            buf.extend((section.len() as u64).to_le_bytes());
+            buf.extend(section);
+        }
+        fs::write(path, buf).unwrap();
+    }
+
+    fn write_challenge(path: &Path, tau: Fr) {
+        let be = |bytes: &[u8]| bytes.iter().rev().copied().collect::<Vec<_>>();
+        let (g1, g2) = powers(tau);
+        let n = 1usize << POWER;
+
+        let write_g1 = |buf: &mut Vec<u8>, p: &G1Affine| {
+            let coordinates = p.coordinates().unwrap();
+            buf.extend(be(&coordinates.x().to_repr()));
+            buf.extend(be(&coordinates.y().to_repr()));
+        };
+        let write_g2 = |buf: &mut Vec<u8>, p: &G2Affine| {
+            let coordinates = p.coordinates().unwrap();
+            for c in [coordinates.x(), coordinates.y()] {
+                buf.extend(be(&c.c1.to_repr()));
+                buf.extend(be(&c.c0.to_repr()));
+            }
+        };
+
+        // hash, tau G1, tau G2, alpha tau G1, beta tau G1, beta G2
+        let mut buf = vec![0u8; 64];
+        g1.iter().for_each(|p| write_g1(&mut buf, p));
+        g2.iter().for_each(|p| write_g2(&mut buf, p));
+        g1[..2 * n].iter().for_each(|p| write_g1(&mut buf, p));
+        write_g2(&mut buf, &g2[0]);
+        fs::write(path, buf).unwrap();
+    }
+
+    #[test]
+    fn import() {
+        let tau = Fr::from(42);
+        let expected = ParamsKZG::<Bn256>::unsafe_setup_with_s(POWER, tau);
+
+        let ptau = env::temp_dir().join("halo2-evm-verifier-test.ptau");
+        write_ptau(&ptau, tau);
+        let challenge = env::temp_dir().join("halo2-evm-verifier-test-challenge");
+        write_challenge(&challenge, tau);
+
+        for path in [ptau, challenge] {
+            let params = import_srs(&path, POWER).unwrap();
+            assert_eq!(params.k(), POWER);
+            assert_eq!(params.get_g(), expected.get_g());
+            assert_eq!(params.s_g2(), expected.s_g2());
+
+            let params = import_srs(&path, POWER - 1).unwrap();
+            assert_eq!(params.get_g(), &expected.get_g()[..1 << (POWER - 1)]);
+
+            assert!(matches!(
+                import_srs(&path, POWER + 1),
+                Err(Error::InvalidK(_))
+            ));
+        }
+
+        // only the first powers match [s]G2, the rest are not powers of the same secret
+        let (mut g1, g2) = powers(tau);
+        g1.truncate(1 << POWER);
+        g1.swap(2, 3);
+        assert!(matches!(
+            params_from_powers(POWER, g1, g2[0], g2[1]),
+            Err(Error::Srs(_))
+        ));
+    }
+
+    #[test]
+    fn ceremony() {
+        let params0 = ParamsKZG::<Bn256>::setup(POWER, OsRng);
+        let (params1, contribution1) = contribute(&params0, OsRng).unwrap();
+        let (params2, contribution2) = contribute(&params1, OsRng).unwrap();
+
+        let mut buf = Vec::new();
+        contribution2.write(&mut buf).unwrap();
+        assert_eq!(
+            Contribution::read(&mut buf.as_slice()).unwrap(),
+            contribution2
+        );
+
+        let chain = [params0.clone(), params1, params2];
+        let contributions = [contribution1.clone(), contribution2.clone()];
+        verify_ceremony(&chain, &contributions).unwrap();
+
+        assert!(verify_ceremony(&chain, &[contribution2, contribution1.clone()]).is_err());
+        assert!(verify_ceremony(&chain[..2], &contributions).is_err());
+
+        // a contribution claiming params it didn't produce
+        let (forged, _) = contribute(&params0, OsRng).unwrap();
+        assert!(verify_ceremony(&[params0.clone(), forged], &[contribution1.clone()]).is_err());
+
+        // the secret must be known and bound to the previous params
+        let mut unproven = contribution1.clone();
+        unproven.z += Fr::ONE;
+        assert!(verify_ceremony(&chain[..2], &[unproven]).is_err());
+        let mut replayed = contribution1;
+        replayed.previous = [0u8; 32];
+        assert!(!replayed.verify_knowledge());
+    }
+}